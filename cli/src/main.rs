@@ -1,12 +1,27 @@
 use clap::{Args, Parser};
 use comfy_table::Table;
-use std::{fmt::Write as _, path::PathBuf};
+use std::{
+	collections::BTreeMap,
+	fmt::Write as _,
+	path::{Path, PathBuf},
+};
 
 use subweight_core::{
-	compare_commits, compare_files, filter_changes,
-	parse::pallet::{parse_files, try_parse_files},
-	sort_changes, CompareParams, Dimension, FilterParams, Percent, RelativeChange, TotalDiff,
-	VERSION,
+	compare_commits, compare_files, compare_runtime_dirs, evaluate_extrinsic, filter_changes,
+	lint::{lint_files, Finding, Lint, LintParams, Rule, Severity},
+	metadata_mismatches,
+	parse::{
+		pallet::{BenchmarkSummary, ChromaticExtrinsic, ComponentRange, ImplParams},
+		parse_files, parse_files_in_repo, report_file, try_parse_files, try_parse_files_in_repo,
+		FileReport,
+	},
+	parse_commits,
+	runtime::{compare_runtimes, RuntimeMatrix, RuntimeParams, RuntimeRow},
+	sort_changes,
+	term::ChromaticTerm,
+	traits::Weight,
+	CompareMethod, CompareParams, Dimension, ExtrinsicDiff, FailOn, FilterParams, Percent,
+	RelativeChange, Revision, SummaryDiff, TermDiff, TotalDiff, VERSION,
 };
 
 #[derive(Debug, Parser)]
@@ -20,11 +35,13 @@ struct MainCmd {
 }
 
 #[derive(Debug, clap::Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum SubCommand {
 	#[clap(subcommand)]
 	Compare(CompareCmd),
 	#[clap(subcommand)]
 	Parse(ParseCmd),
+	Lint(LintCmd),
 }
 
 /// Compare weight files.
@@ -32,6 +49,7 @@ enum SubCommand {
 enum CompareCmd {
 	Files(CompareFilesCmd),
 	Commits(CompareCommitsCmd),
+	Runtimes(CompareRuntimesCmd),
 }
 
 /// Tries to parse all files in the given file list or folder.
@@ -40,6 +58,36 @@ enum ParseCmd {
 	Files(ParseFilesCmd),
 }
 
+/// Checks weight files for common mistakes.
+#[derive(Debug, Parser)]
+struct LintCmd {
+	/// The files to lint.
+	#[clap(index = 1, required(true), num_args = 0..1000)]
+	pub files: Vec<PathBuf>,
+
+	/// Set the format of the findings.
+	#[clap(long, value_name = "FORMAT", default_value = "human", ignore_case = true)]
+	pub format: LintFormat,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub params: LintParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub impls: ImplParams,
+}
+
+#[derive(Debug, clap::ValueEnum, Clone, Eq, PartialEq, Copy)]
+pub enum LintFormat {
+	/// One diagnostic per finding.
+	Human,
+	/// Json list of the findings.
+	Json,
+	/// SARIF 2.1.0 log for code scanning tools.
+	Sarif,
+}
+
 /// Compare a local set of weight files.
 #[derive(Debug, Parser)]
 struct CompareFilesCmd {
@@ -55,6 +103,14 @@ struct CompareFilesCmd {
 	#[clap(flatten)]
 	pub format: FormatParams,
 
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub gate: GateParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub runtimes: RuntimeParams,
+
 	/// The old weight files.
 	#[clap(long, required(true), num_args = 0..)]
 	pub old: Vec<PathBuf>,
@@ -79,6 +135,14 @@ struct CompareCommitsCmd {
 	#[clap(flatten)]
 	pub format: FormatParams,
 
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub gate: GateParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub runtimes: RuntimeParams,
+
 	/// Old commit/branch/tag.
 	#[clap(name = "OLD-COMMIT", index = 1)]
 	pub old: String,
@@ -94,11 +158,102 @@ struct CompareCommitsCmd {
 	pub path_pattern: String,
 }
 
+/// Compare the weight files of two runtimes at the same revision.
+#[derive(Debug, Parser)]
+struct CompareRuntimesCmd {
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub params: CompareParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub filter: FilterParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub format: FormatParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub gate: GateParams,
+
+	/// Directory of the runtime that is shown as old, for example `runtime/kusama`.
+	#[clap(long)]
+	pub left: PathBuf,
+
+	/// Directory of the runtime that is shown as new, for example `runtime/polkadot`.
+	#[clap(long)]
+	pub right: PathBuf,
+
+	/// Commit/branch/tag to check out first. Uses the working tree if not set.
+	#[clap(long)]
+	pub rev: Option<String>,
+
+	#[clap(long, default_value = ".")]
+	pub repo: PathBuf,
+
+	/// Weight files of each runtime, relative to its directory.
+	#[clap(long, default_value = "src/weights/**/*.rs")]
+	pub path_pattern: String,
+}
+
+/// Parses weight files and reports their kind and extrinsics.
 #[derive(Debug, Parser)]
 struct ParseFilesCmd {
 	/// The files to parse.
-	#[clap(long, index = 1, required(true), num_args = 0..1000)]
+	#[clap(index = 1, required(true), num_args = 0..1000)]
 	pub files: Vec<PathBuf>,
+
+	/// Print a table of the files or dump all parsed extrinsics.
+	#[clap(long, value_name = "FORMAT", default_value = "human", ignore_case = true)]
+	pub format: ParseFormat,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub impls: ImplParams,
+}
+
+#[derive(Debug, clap::ValueEnum, Clone, Eq, PartialEq, Copy)]
+pub enum ParseFormat {
+	/// Table with one row per file.
+	Human,
+	/// Json list of the parsed extrinsics.
+	Json,
+	/// Yaml list of the parsed extrinsics.
+	Yaml,
+}
+
+/// An extrinsic as dumped by `subweight parse files --format json`.
+#[derive(Debug, serde::Serialize)]
+struct ParsedExtrinsic {
+	pallet: String,
+	name: String,
+	term: ChromaticTerm,
+	comp_ranges: Option<BTreeMap<String, ComponentRange>>,
+	lints: Vec<Lint>,
+	/// Weight with all components at their minimum.
+	base: Option<Weight>,
+	/// Largest weight within the component ranges.
+	worst: Option<Weight>,
+	/// Why `base` or `worst` could not be evaluated.
+	error: Option<subweight_core::Error>,
+}
+
+impl From<ChromaticExtrinsic> for ParsedExtrinsic {
+	fn from(ext: ChromaticExtrinsic) -> Self {
+		let eval = |method| evaluate_extrinsic(&ext, method, Default::default());
+		let (base, worst) = (eval(CompareMethod::Base), eval(CompareMethod::ExactWorst));
+		Self {
+			error: base.as_ref().err().or(worst.as_ref().err()).cloned(),
+			base: base.ok(),
+			worst: worst.ok(),
+			pallet: ext.pallet,
+			name: ext.name,
+			term: ext.term,
+			comp_ranges: ext.comp_ranges.map(|r| r.into_iter().collect()),
+			lints: ext.lints,
+		}
+	}
 }
 
 /// Parameters for modifying the output representation.
@@ -114,6 +269,26 @@ pub struct FormatParams {
 	#[clap(long)]
 	print_terms: bool,
 
+	/// Include the old and new coefficient of each monomial of the weight terms.
+	///
+	/// Shows whether the base weight (`constant`) or the slope of a component like `n` or
+	/// `n * READ` changed.
+	#[clap(long)]
+	print_coefficients: bool,
+
+	/// Include the changed storage accesses of each extrinsic in the console output.
+	///
+	/// They are parsed from the `Storage:` and `Proof:` doc comments of the weight functions.
+	#[clap(long)]
+	print_storage: bool,
+
+	/// Include the minimum execution time and the measured and estimated proof size.
+	///
+	/// They are parsed from the comments in the weight functions and shown next to the fitted
+	/// weight.
+	#[clap(long)]
+	print_summary: bool,
+
 	/// Disable color output.
 	#[clap(long)]
 	no_color: bool,
@@ -125,6 +300,17 @@ pub struct FormatParams {
 	strip_path_prefix: Option<String>,
 }
 
+/// Parameters for failing a CI run.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct GateParams {
+	/// Exit with a non-zero code if any of these policies is violated.
+	///
+	/// Possible values: `failed`, `warning`, `changed`, `changed:<PERCENT>`, `added` and
+	/// `removed`. Each one has its own exit code. Can be repeated or comma separated.
+	#[clap(long, value_name = "POLICY", action = clap::ArgAction::Append, value_delimiter = ',')]
+	pub fail_on: Vec<FailOn>,
+}
+
 impl FormatParams {
 	pub fn filter_path(&self, path: String) -> String {
 		if let Some(prefix) = self.strip_path_prefix.as_ref() {
@@ -175,7 +361,17 @@ impl std::str::FromStr for OutputFormat {
 	}
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() {
+	if let Err(err) = run() {
+		match err.downcast_ref::<subweight_core::Error>() {
+			Some(err) => eprintln!("{}", render_diagnostic(err)),
+			None => eprintln!("Error: {}", err),
+		}
+		std::process::exit(1);
+	}
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
 	let cmd = MainCmd::parse();
 
 	// TODO is is good to not set this up at all?!
@@ -187,49 +383,480 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 	match cmd.subcommand {
 		SubCommand::Compare(CompareCmd::Files(CompareFilesCmd {
-			params,
+			mut params,
 			filter,
 			format,
+			gate,
+			runtimes,
 			old,
 			new,
 		})) => {
-			let olds =
-				if params.ignore_errors { try_parse_files(&old) } else { parse_files(&old)? };
-			let news =
-				if params.ignore_errors { try_parse_files(&new) } else { parse_files(&new)? };
+			// The runtime is captured from the path, so the pallets must keep it.
+			let parse = |files: &[PathBuf]| match (&runtimes.runtime_pattern, params.ignore_errors)
+			{
+				(None, true) => Ok(try_parse_files(files, &params.impls)),
+				(None, false) => parse_files(files, &params.impls),
+				(Some(_), true) =>
+					Ok(try_parse_files_in_repo(Path::new("."), files, &params.impls)),
+				(Some(_), false) => parse_files_in_repo(Path::new("."), files, &params.impls),
+			};
+			let olds = parse(&old)?;
+			let news = parse(&new)?;
+			params.load(Revision::Old, Path::new("."))?;
+			params.load(Revision::New, Path::new("."))?;
 
+			if let Some(pattern) = runtimes.runtime_pattern.as_ref() {
+				let matrix = compare_runtimes(olds, news, &params, &filter, pattern)?;
+				std::process::exit(finish_runtimes(matrix, &filter, cmd.verbose, format, &gate)?);
+			}
 			let mut diff = compare_files(olds, news, &params, &filter)?;
 			diff = filter_changes(diff, &filter);
 			sort_changes(&mut diff);
 			diff.reverse();
-			print_changes(diff, cmd.verbose, format, params.unit)?;
+			print_changes(diff.clone(), cmd.verbose, format, params.unit)?;
+			print_warnings(&diff);
+			std::process::exit(exit_code(&diff, &gate));
 		},
 		SubCommand::Compare(CompareCmd::Commits(CompareCommitsCmd {
 			params,
 			filter,
 			format,
+			gate,
+			runtimes,
 			old,
 			new,
 			repo,
 			path_pattern,
 		})) => {
+			if let Some(pattern) = runtimes.runtime_pattern.as_ref() {
+				let (olds, news, params) =
+					parse_commits(&repo, &old, &new, &params, &path_pattern, usize::MAX)?;
+				let matrix = compare_runtimes(olds, news, &params, &filter, pattern)?;
+				std::process::exit(finish_runtimes(matrix, &filter, cmd.verbose, format, &gate)?);
+			}
 			let mut diff =
 				compare_commits(&repo, &old, &new, &params, &filter, &path_pattern, usize::MAX)?;
 			diff = filter_changes(diff, &filter);
 			sort_changes(&mut diff);
 			diff.reverse();
-			print_changes(diff, cmd.verbose, format, params.unit)?;
+			print_changes(diff.clone(), cmd.verbose, format, params.unit)?;
+			print_warnings(&diff);
+			std::process::exit(exit_code(&diff, &gate));
 		},
-		SubCommand::Parse(ParseCmd::Files(ParseFilesCmd { files })) => {
-			print(format!("Trying to parse {} files...", files.len()), cmd.verbose);
-			let parsed = parse_files(&files)?;
-			print(format!("Parsed {} files successfully", parsed.len()), cmd.verbose);
+		SubCommand::Compare(CompareCmd::Runtimes(CompareRuntimesCmd {
+			params,
+			filter,
+			format,
+			gate,
+			left,
+			right,
+			rev,
+			repo,
+			path_pattern,
+		})) => {
+			let mut diff = compare_runtime_dirs(
+				&repo,
+				rev.as_deref(),
+				&left,
+				&right,
+				&params,
+				&filter,
+				&path_pattern,
+			)?;
+			diff = filter_changes(diff, &filter);
+			sort_changes(&mut diff);
+			diff.reverse();
+			print_changes(diff.clone(), cmd.verbose, format, params.unit)?;
+			print_warnings(&diff);
+			std::process::exit(exit_code(&diff, &gate));
+		},
+		SubCommand::Parse(ParseCmd::Files(ParseFilesCmd { files, format, impls })) => {
+			// Keep stdout machine readable for the dump formats.
+			let print = |msg: String| match format {
+				ParseFormat::Human => print(msg, cmd.verbose),
+				_ => eprintln!("{}", msg),
+			};
+			print(format!("Trying to parse {} files...", files.len()));
+			let (reports, extrinsics): (Vec<_>, Vec<_>) =
+				files.iter().map(|f| report_file(f, &impls)).unzip();
+			let extrinsics = extrinsics.into_iter().flatten().map(ParsedExtrinsic::from);
+			match format {
+				ParseFormat::Human => println!("{}", print_reports_human(&reports)),
+				ParseFormat::Json =>
+					println!("{}", serde_json::to_string_pretty(&extrinsics.collect::<Vec<_>>())?),
+				ParseFormat::Yaml =>
+					print!("{}", serde_yaml::to_string(&extrinsics.collect::<Vec<_>>())?),
+			}
+			let errors = reports.iter().filter_map(|r| r.error.as_ref()).collect::<Vec<_>>();
+			for err in &errors {
+				eprintln!("{}", render_diagnostic(err));
+			}
+
+			let parsed = reports.len() - errors.len();
+			print(format!("Parsed {} of {} files successfully", parsed, reports.len()));
+			if !errors.is_empty() {
+				std::process::exit(1);
+			}
+		},
+		SubCommand::Lint(LintCmd { files, format, params, impls }) => {
+			let findings = lint_files(&files, &impls, &params)?;
+			match format {
+				LintFormat::Human => print!("{}", print_findings_human(&findings)),
+				LintFormat::Json => println!("{}", serde_json::to_string_pretty(&findings)?),
+				LintFormat::Sarif =>
+					println!("{}", serde_json::to_string_pretty(&print_findings_sarif(&findings))?),
+			}
+			if findings.iter().any(|f| f.severity == Severity::Error) {
+				std::process::exit(1);
+			}
 		},
 	}
 
 	Ok(())
 }
 
+/// Renders an error like a compiler diagnostic.
+///
+/// Parse errors with a location show the offending line of the source:
+///
+/// ```text
+/// error: Unknown call: frobnicate
+///  --> weights.rs:5:5
+///   |
+/// 5 |             .frobnicate(2)
+///   |             ^^^^^^^^^^
+/// ```
+///
+/// Files of an unknown kind list the reason of each parser below the file name.
+fn render_diagnostic(err: &subweight_core::Error) -> String {
+	if let subweight_core::Error::UnknownFile { file, rejections } = err {
+		let mut out = format!(
+			"error: {}
+ --> {}",
+			err.message(),
+			file
+		);
+		for rejection in rejections {
+			let _ = write!(out, "\n  = {}: {}", rejection.parser, rejection.error.message());
+			if let Some(snippet) = render_snippet(&rejection.error) {
+				for line in snippet.lines() {
+					let _ = write!(out, "\n    {}", line);
+				}
+			}
+		}
+		return out
+	}
+
+	match render_snippet(err) {
+		Some(snippet) => format!("error: {}\n{}", err.message(), snippet),
+		None => format!("Error: {}", err),
+	}
+}
+
+/// The location of an error and the line of source that it points to.
+fn render_snippet(err: &subweight_core::Error) -> Option<String> {
+	let (file, span) = match err.location() {
+		Some((file, Some(span))) => (file, span),
+		_ => return None,
+	};
+	let line = std::fs::read_to_string(file)
+		.ok()
+		.and_then(|content| content.lines().nth(span.line.saturating_sub(1)).map(String::from));
+	let Some(line) = line else {
+		return Some(format!("  --> {}:{}:{}", file, span.line, span.column + 1))
+	};
+
+	let number = span.line.to_string();
+	let pad = " ".repeat(number.len());
+	let len = if span.end_line == span.line {
+		span.end_column.saturating_sub(span.column)
+	} else {
+		line.chars().count().saturating_sub(span.column)
+	};
+	let mut out = String::new();
+	let _ = writeln!(out, "{}--> {}:{}:{}", pad, file, span.line, span.column + 1);
+	let _ = writeln!(out, "{} |", pad);
+	let _ = writeln!(out, "{} | {}", number, line);
+	// Keep the tabs of the source line so that the markers line up.
+	let indent: String = line
+		.chars()
+		.take(span.column)
+		.map(|c| if c == '\t' { '\t' } else { ' ' })
+		.collect();
+	let _ = write!(out, "{} | {}{}", pad, indent, "^".repeat(len.max(1)));
+	Some(out)
+}
+
+/// Prints each finding like a compiler diagnostic and a summary:
+///
+/// ```text
+/// warning[missing-range]: Component 's' has no range annotation
+///  --> weights/pallet_staking.rs:160
+///   = extrinsic: withdraw_unbonded_update
+///
+/// Found 0 errors, 1 warning and 0 notes
+/// ```
+fn print_findings_human(findings: &[Finding]) -> String {
+	let mut out = String::new();
+	for finding in findings {
+		let _ = writeln!(out, "{}[{}]: {}", finding.severity, finding.rule, finding.message);
+		let line = finding.line.map(|l| format!(":{}", l)).unwrap_or_default();
+		let _ = writeln!(out, " --> {}{}", finding.file, line);
+		if let Some(extrinsic) = &finding.extrinsic {
+			let _ = writeln!(out, "  = extrinsic: {}", extrinsic);
+		}
+		out.push('\n');
+	}
+
+	let count = |severity: Severity, name: &str| {
+		let n = findings.iter().filter(|f| f.severity == severity).count();
+		format!("{} {}{}", n, name, if n == 1 { "" } else { "s" })
+	};
+	let _ = writeln!(
+		out,
+		"Found {}, {} and {}",
+		count(Severity::Error, "error"),
+		count(Severity::Warning, "warning"),
+		count(Severity::Note, "note")
+	);
+	out
+}
+
+/// Converts the findings into a SARIF 2.1.0 log with one run.
+///
+/// All rules are listed in the driver so that code scanning tools can show their description.
+fn print_findings_sarif(findings: &[Finding]) -> serde_json::Value {
+	let rules = Rule::all()
+		.iter()
+		.map(|rule| {
+			serde_json::json!({
+				"id": rule.id(),
+				"shortDescription": { "text": rule.description() },
+				"defaultConfiguration": { "level": rule.severity().to_string() },
+			})
+		})
+		.collect::<Vec<_>>();
+	let results = findings
+		.iter()
+		.map(|finding| {
+			let mut location = serde_json::json!({
+				"artifactLocation": { "uri": finding.file.replace('\\', "/") },
+			});
+			if let Some(line) = finding.line {
+				location["region"] = serde_json::json!({ "startLine": line });
+			}
+			serde_json::json!({
+				"ruleId": finding.rule.id(),
+				"level": finding.severity.to_string(),
+				"message": { "text": finding.message },
+				"locations": [{ "physicalLocation": location }],
+			})
+		})
+		.collect::<Vec<_>>();
+
+	serde_json::json!({
+		"$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+		"version": "2.1.0",
+		"runs": [{
+			"tool": {
+				"driver": {
+					"name": "subweight",
+					"version": env!("CARGO_PKG_VERSION"),
+					"informationUri": env!("CARGO_PKG_REPOSITORY"),
+					"rules": rules,
+				}
+			},
+			"results": results,
+		}],
+	})
+}
+
+/// Prints one row per file with its kind, number of extrinsics and lints, warnings and error.
+fn print_reports_human(reports: &[FileReport]) -> String {
+	let mut table = Table::new();
+	table.set_constraints(vec![comfy_table::ColumnConstraint::ContentWidth]);
+	table.set_header(vec!["File", "Kind", "Extrinsics", "Lints", "Warnings", "Error"]);
+	for report in reports {
+		table.add_row(vec![
+			report.file.clone(),
+			report.kind.map(|k| k.to_string()).unwrap_or_default(),
+			report.extrinsics.to_string(),
+			report.lints.to_string(),
+			report.warnings.join("\n"),
+			report.error.as_ref().map(|e| e.message()).unwrap_or_default(),
+		]);
+	}
+	table.to_string()
+}
+
+/// Prints all distinct warnings to stderr.
+///
+/// Warnings about the benchmark metadata are the same for all extrinsics of a file and are
+/// therefore only printed once.
+fn print_warnings(diff: &TotalDiff) {
+	for (file, mismatches) in metadata_mismatches(diff) {
+		for mismatch in mismatches {
+			eprintln!("Warning: {}: {}", file, mismatch);
+		}
+	}
+	// Chromatic extrinsics can have the same warning in both dimensions.
+	let warnings = diff
+		.iter()
+		.flat_map(|d| d.warnings())
+		.collect::<std::collections::BTreeSet<_>>();
+	for warning in warnings {
+		eprintln!("Warning: {}", warning);
+	}
+}
+
+/// Exit code when any extrinsic exceeds the block budget.
+///
+/// Clap already exits with `2` on usage errors, so this is above all [`policy_exit_code`]s.
+const EXIT_OVER_BUDGET: i32 = 8;
+
+/// Exit code when a [`FailOn`] policy is violated.
+fn policy_exit_code(policy: &FailOn) -> i32 {
+	match policy {
+		FailOn::Failed => 3,
+		FailOn::Warning => 4,
+		FailOn::Changed(_) => 5,
+		FailOn::Added => 6,
+		FailOn::Removed => 7,
+	}
+}
+
+/// Reports all block budget and policy violations and returns the exit code.
+///
+/// The lowest exit code of all violations is returned; the policies in the order of their exit
+/// codes and then the budget.
+fn exit_code(diff: &TotalDiff, gate: &GateParams) -> i32 {
+	let mut code = 0;
+
+	let over = diff.iter().flat_map(|d| d.over_budget()).collect::<Vec<_>>();
+	if !over.is_empty() {
+		eprintln!("{} extrinsics exceed the block budget:", over.len());
+		for msg in &over {
+			eprintln!("  {}", msg);
+		}
+	}
+
+	let mut policies = gate.fail_on.clone();
+	policies.sort_by_key(policy_exit_code);
+	for policy in policies {
+		let offenders = policy.offenders(diff);
+		// Metadata mismatches belong to a whole file and not to its extrinsics.
+		let files = match policy {
+			FailOn::Warning => metadata_mismatches(diff),
+			_ => Default::default(),
+		};
+		if offenders.is_empty() && files.is_empty() {
+			continue
+		}
+
+		if !offenders.is_empty() {
+			eprintln!("Policy '{}' is violated by {} extrinsics:", policy, offenders.len());
+		}
+		for offender in offenders {
+			let change = offender
+				.term()
+				.map(|t| color_percent(t.percent, &t.change, true))
+				.unwrap_or_else(|| "ERROR".into());
+			eprintln!("  {}::{} ({})", offender.file, offender.name, change);
+		}
+		if !files.is_empty() {
+			eprintln!("Policy '{}' is violated by {} files:", policy, files.len());
+		}
+		for (file, mismatches) in files {
+			eprintln!("  {} ({})", file, mismatches.join(", "));
+		}
+		if code == 0 {
+			code = policy_exit_code(&policy);
+		}
+	}
+
+	if code == 0 && !over.is_empty() {
+		code = EXIT_OVER_BUDGET;
+	}
+	code
+}
+
+/// Filters, sorts and prints the runtime matrix and returns the exit code.
+fn finish_runtimes(
+	matrix: RuntimeMatrix,
+	filter: &FilterParams,
+	verbose: bool,
+	format: FormatParams,
+	gate: &GateParams,
+) -> Result<i32, Box<dyn std::error::Error>> {
+	let mut matrix = matrix.filter(filter);
+	matrix.sort();
+	matrix.rows.reverse();
+	print_matrix(&matrix, verbose, format)?;
+
+	let diff = matrix.diffs();
+	print_warnings(&diff);
+	Ok(exit_code(&diff, gate))
+}
+
+/// Prints one row per extrinsic and the relative change of each runtime as column.
+fn print_matrix(
+	matrix: &RuntimeMatrix,
+	verbose: bool,
+	format: FormatParams,
+) -> Result<(), Box<dyn std::error::Error>> {
+	if format.format == OutputFormat::JSON {
+		println!("{}", serde_json::to_string_pretty(matrix)?);
+		return Ok(())
+	}
+	if matrix.is_empty() {
+		print("No changes found.".into(), verbose);
+		return Ok(())
+	}
+	// CSV is read by other tools, so it never has colors.
+	let no_color = format.no_color || format.format == OutputFormat::CSV;
+	let cells = |row: &RuntimeRow| {
+		matrix
+			.runtimes
+			.iter()
+			.map(|runtime| match row.changes.get(runtime) {
+				Some(diff) => match diff.term() {
+					Some(t) => color_percent(t.percent, &t.change, no_color),
+					None => "ERROR".into(),
+				},
+				None => "-".into(),
+			})
+			.collect::<Vec<_>>()
+	};
+
+	let mut header = vec!["File".to_string(), "Extrinsic".to_string()];
+	header.extend(matrix.runtimes.iter().cloned());
+	match format.format {
+		OutputFormat::CSV => {
+			let mut output = header.join(",");
+			for row in &matrix.rows {
+				write!(output, "\n{},{},{}", row.file, row.name, cells(row).join(","))?;
+			}
+			println!("{}", output);
+		},
+		OutputFormat::Human | OutputFormat::Markdown => {
+			let mut table = Table::new();
+			table.set_constraints(vec![comfy_table::ColumnConstraint::ContentWidth]);
+			if format.format == OutputFormat::Markdown {
+				table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+			}
+			table.set_header(header);
+			for row in &matrix.rows {
+				let mut cols = vec![format.filter_path(row.file.clone()), row.name.clone()];
+				cols.extend(cells(row));
+				table.add_row(cols);
+			}
+			println!("{}", table);
+		},
+		_ => return Err("Unsupported output format".into()),
+	}
+	Ok(())
+}
+
 fn print_changes(
 	per_extrinsic: TotalDiff,
 	verbose: bool,
@@ -240,13 +867,67 @@ fn print_changes(
 		OutputFormat::Human => print_changes_human(per_extrinsic, verbose, format, unit, false),
 		OutputFormat::Markdown => print_changes_human(per_extrinsic, verbose, format, unit, true),
 		OutputFormat::CSV => print_changes_csv(per_extrinsic, verbose, format, unit),
-		_ => Err("Unsupported output format".into()),
+		OutputFormat::BriefHuman => print_changes_brief(per_extrinsic, verbose, format, unit),
+		OutputFormat::JSON => print_changes_json(per_extrinsic),
 	};
 
 	println!("{}", output?);
 	Ok(())
 }
 
+/// Prints the changes as JSON array of [`subweight_core::ExtrinsicDiff`].
+///
+/// The format is documented in the README under *Example: JSON Output*.
+fn print_changes_json(per_extrinsic: TotalDiff) -> Result<String, Box<dyn std::error::Error>> {
+	Ok(serde_json::to_string_pretty(&per_extrinsic)?)
+}
+
+/// Prints one line per extrinsic with its old and new value and the relative change.
+fn print_changes_brief(
+	per_extrinsic: TotalDiff,
+	verbose: bool,
+	format: FormatParams,
+	unit: Dimension,
+) -> Result<String, Box<dyn std::error::Error>> {
+	if per_extrinsic.is_empty() {
+		print("No changes found.".into(), verbose);
+		return Ok(String::new())
+	}
+	let cell = |diff: &TermDiff, unit: Dimension| match diff.term() {
+		Some(change) => {
+			let fmt = |v: Option<u128>| v.map_or("-".into(), |v| unit.fmt_value(v));
+			format!(
+				"{} -> {} ({})",
+				fmt(change.old_v),
+				fmt(change.new_v),
+				color_percent(change.percent, &change.change, format.no_color)
+			)
+		},
+		None => "ERROR".into(),
+	};
+
+	let lines = per_extrinsic
+		.iter()
+		.map(|info| {
+			let change = match &info.chromatic {
+				Some(chromatic) => format!(
+					"time {}, proof {}",
+					cell(&chromatic.time, Dimension::Time),
+					cell(&chromatic.proof, Dimension::Proof)
+				),
+				None => cell(&info.change, unit),
+			};
+			format!(
+				"{}::{}: {}",
+				format.filter_path(info.file.clone()),
+				extrinsic_cell(info),
+				change
+			)
+		})
+		.collect::<Vec<_>>();
+	Ok(lines.join("\n"))
+}
+
 // TODO make meta output format
 fn print_changes_csv(
 	per_extrinsic: TotalDiff,
@@ -261,20 +942,37 @@ fn print_changes_csv(
 
 	let mut output = String::new();
 	// Put a csv header
-	output.push_str("File,Extrinsic,Old,New,Change Percent");
+	if per_extrinsic.iter().any(|d| d.chromatic.is_some()) {
+		output.push_str(
+			"File,Extrinsic,Old Time,New Time,Time Change Percent,Old Proof,New Proof,Proof Change \
+			 Percent",
+		);
+	} else {
+		output.push_str("File,Extrinsic,Old,New,Change Percent");
+	}
 	if format.print_terms {
 		output.push_str(",Old Weight Term,New Weight Term,Used variables");
 	}
+	if format.print_coefficients {
+		output.push_str(",Coefficients");
+	}
+	if format.print_summary {
+		for value in SUMMARY_VALUES.iter().map(|(name, ..)| name) {
+			write!(output, ",Old {},New {},{} Change Percent", value, value, value)?;
+		}
+		output.push_str(",Proof Estimation Percent");
+	}
+	if format.print_storage {
+		output.push_str(",Storage");
+	}
 	output.push('\n');
 
 	for (info, change) in per_extrinsic.iter().filter_map(|p| p.term().map(|t| (p, t))) {
 		let mut row = format!(
-			"{},{},{},{},{}",
+			"{},{},{}",
 			info.file.clone(),
 			info.name.clone(),
-			change.old_v.map(|v| unit.fmt_value(v)).unwrap_or_default(),
-			change.new_v.map(|v| unit.fmt_value(v)).unwrap_or_default(),
-			color_percent(change.percent, &change.change, format.no_color),
+			human_cells(info, unit, format.no_color).join(","),
 		);
 
 		if format.print_terms {
@@ -290,6 +988,26 @@ fn print_changes_csv(
 			)?;
 			row.push_str(&format!("{:?}", &change.scope).replace(',', " "));
 		}
+		if format.print_coefficients {
+			write!(row, ",{}", coefficients_cell(info, "; ", format.no_color))?;
+		}
+		if format.print_summary {
+			for (_, value, unit) in SUMMARY_VALUES {
+				let cells = match info.summary.as_ref().and_then(|s| s.compare(value)) {
+					Some(c) => vec![
+						c.old.map(|v| unit.fmt_value(v)).unwrap_or_default(),
+						c.new.map(|v| unit.fmt_value(v)).unwrap_or_default(),
+						color_percent(c.percent, &c.change, format.no_color),
+					],
+					None => vec![String::new(); 3],
+				};
+				write!(row, ",{}", cells.join(","))?;
+			}
+			write!(row, ",{}", estimation_cell(info, format.no_color))?;
+		}
+		if format.print_storage {
+			write!(row, ",{}", storage_cell(info, "; "))?;
+		}
 		row.push('\n');
 		output.push_str(&row);
 	}
@@ -314,32 +1032,45 @@ fn print_changes_human(
 	if markdown {
 		table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
 	}
-	let mut header = vec!["File", "Extrinsic", "Old", "New", "Change [%]"];
+	let chromatic = per_extrinsic.iter().any(|d| d.chromatic.is_some());
+	let mut header = vec!["File", "Extrinsic"];
+	if chromatic {
+		header.extend(vec![
+			"Old Time",
+			"New Time",
+			"Time [%]",
+			"Old Proof",
+			"New Proof",
+			"Proof [%]",
+		]);
+	} else {
+		header.extend(vec!["Old", "New", "Change [%]"]);
+	}
 	if format.print_terms {
 		header.extend(vec!["Old Weight Term", "New Weight Term", "Used variables"]);
 	}
+	if format.print_coefficients {
+		header.push("Coefficients");
+	}
+	if format.print_summary {
+		header.extend(SUMMARY_VALUES.iter().map(|(name, ..)| *name));
+		header.push("Proof Estimation");
+	}
+	if format.print_storage {
+		header.push("Storage");
+	}
 	table.set_header(header);
 
 	// Print all errors
-	for (info, _change) in per_extrinsic.iter().filter_map(|p| p.error().map(|t| (p, t))) {
-		let row = vec![
-			format.filter_path(info.file.clone()),
-			info.name.clone(),
-			"-".into(),
-			"-".into(),
-			"ERROR".into(),
-		];
+	for info in per_extrinsic.iter().filter(|p| p.error().is_some()) {
+		let mut row = vec![format.filter_path(info.file.clone()), info.name.clone()];
+		row.extend(human_cells(info, unit, format.no_color));
 		table.add_row(row);
 	}
 
 	for (info, change) in per_extrinsic.iter().filter_map(|p| p.term().map(|t| (p, t))) {
-		let mut row = vec![
-			format.filter_path(info.file.clone()),
-			info.name.clone(),
-			change.old_v.map(|v| unit.fmt_value(v)).unwrap_or_default(),
-			change.new_v.map(|v| unit.fmt_value(v)).unwrap_or_default(),
-			color_percent(change.percent, &change.change, format.no_color),
-		];
+		let mut row = vec![format.filter_path(info.file.clone()), extrinsic_cell(info)];
+		row.extend(human_cells(info, unit, format.no_color));
 
 		if format.print_terms {
 			row.extend(vec![
@@ -348,11 +1079,103 @@ fn print_changes_human(
 				format!("{:?}", &change.scope),
 			]);
 		}
+		if format.print_coefficients {
+			let sep = if markdown { "<br>" } else { "\n" };
+			row.push(coefficients_cell(info, sep, format.no_color));
+		}
+		if format.print_summary {
+			for (_, value, unit) in SUMMARY_VALUES {
+				let change = info.summary.as_ref().and_then(|s| s.compare(value));
+				row.push(change.map_or("-".into(), |c| {
+					let fmt = |v: Option<u128>| v.map_or("-".into(), |v| unit.fmt_value(v));
+					format!(
+						"{} -> {} ({})",
+						fmt(c.old),
+						fmt(c.new),
+						color_percent(c.percent, &c.change, format.no_color)
+					)
+				}));
+			}
+			row.push(estimation_cell(info, format.no_color));
+		}
+		if format.print_storage {
+			row.push(storage_cell(info, if markdown { "<br>" } else { "\n" }));
+		}
 		table.add_row(row);
 	}
 	Ok(table.to_string())
 }
 
+/// The name of an extrinsic and where it came from if it was renamed or moved.
+fn extrinsic_cell(info: &ExtrinsicDiff) -> String {
+	match &info.renamed_from {
+		Some(r) if r.is_move() => format!("{} (moved from {})", info.name, r.old_file),
+		Some(r) => format!("{} (renamed from {})", info.name, r.old_name),
+		None => info.name.clone(),
+	}
+}
+
+/// The old, new and relative change cells of an extrinsic; per dimension if it is chromatic.
+fn human_cells(info: &ExtrinsicDiff, unit: Dimension, no_color: bool) -> Vec<String> {
+	let cells = |diff: &TermDiff, unit: Dimension| match diff.term() {
+		Some(change) => vec![
+			change.old_v.map(|v| unit.fmt_value(v)).unwrap_or_default(),
+			change.new_v.map(|v| unit.fmt_value(v)).unwrap_or_default(),
+			color_percent(change.percent, &change.change, no_color),
+		],
+		None => vec!["-".into(), "-".into(), "ERROR".into()],
+	};
+
+	match &info.chromatic {
+		Some(chromatic) => {
+			let mut row = cells(&chromatic.time, Dimension::Time);
+			row.extend(cells(&chromatic.proof, Dimension::Proof));
+			row
+		},
+		None => cells(&info.change, unit),
+	}
+}
+
+/// Name, accessor and unit of the [`BenchmarkSummary`] values for `--print-summary`.
+#[allow(clippy::type_complexity)]
+const SUMMARY_VALUES: [(&str, fn(&BenchmarkSummary) -> Option<u128>, Dimension); 3] = [
+	("Min Time", |s| s.min_time, Dimension::Time),
+	("Measured Proof", |s| s.measured_proof, Dimension::Proof),
+	("Estimated Proof", |s| s.estimated_proof, Dimension::Proof),
+];
+
+/// How much larger the estimated proof size is than the measured one.
+fn estimation_cell(info: &ExtrinsicDiff, no_color: bool) -> String {
+	info.summary
+		.as_ref()
+		.and_then(SummaryDiff::proof_estimation)
+		.map_or("-".into(), |c| color_percent(c.percent, &c.change, no_color))
+}
+
+/// The coefficient changes of an extrinsic in the form `n: 5.00K -> 6.00K (+20.00)` joined by
+/// `sep`.
+fn coefficients_cell(info: &ExtrinsicDiff, sep: &str, no_color: bool) -> String {
+	let fmt = |v: Option<u128>| v.map_or("-".into(), Dimension::fmt_scalar);
+	info.coefficients
+		.iter()
+		.map(|c| {
+			format!(
+				"{}: {} -> {} ({})",
+				c.monomial,
+				fmt(c.change.old),
+				fmt(c.change.new),
+				color_percent(c.change.percent, &c.change.change, no_color)
+			)
+		})
+		.collect::<Vec<_>>()
+		.join(sep)
+}
+
+/// The storage changes of an extrinsic joined by `sep`.
+fn storage_cell(info: &ExtrinsicDiff, sep: &str) -> String {
+	info.storage.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(sep)
+}
+
 fn print(msg: String, verbose: bool) {
 	if verbose {
 		log::info!("{}", msg);
@@ -372,7 +1195,7 @@ pub fn color_percent(p: Percent, change: &RelativeChange, no_color: bool) -> Str
 		RelativeChange::Unchanged => "Unchanged".to_string(),
 		RelativeChange::Added => maybe_color(AnsiColor::Red, "Added", no_color),
 		RelativeChange::Removed => maybe_color(AnsiColor::Green, "Removed", no_color),
-		RelativeChange::Changed => {
+		RelativeChange::Changed | RelativeChange::Renamed => {
 			let s = format!("{:+5.2}", p);
 			match p {
 				x if x < 0.0 => maybe_color(AnsiColor::Green, s, no_color),
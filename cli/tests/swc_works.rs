@@ -158,3 +158,740 @@ fn subweight_compare_files_errors() {
 	let out = String::from_utf8_lossy(&output.stderr).trim().to_owned();
 	assert_contains(&out, "Could not find a weight implementation in the passed file");
 }
+
+#[test]
+fn subweight_compare_files_json_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--format", "json"])
+		.args([
+			"--old",
+			root_dir().join("test_data/old/pallet_staking.rs.txt").to_str().unwrap(),
+			"--new",
+			root_dir().join("test_data/new/pallet_staking.rs.txt").to_str().unwrap(),
+			"--threshold",
+			"0",
+		])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let diffs = out.as_array().unwrap();
+	assert!(!diffs.is_empty());
+
+	for diff in diffs {
+		assert!(diff["file"].as_str().unwrap().ends_with("pallet_staking.rs.txt"));
+		assert!(diff["name"].is_string());
+		let change = &diff["change"]["changed"];
+		assert_eq!(change["method"], "base");
+		assert!(["changed", "unchanged", "added", "removed"]
+			.contains(&change["change"].as_str().unwrap()));
+		assert!(change["scope"].is_object());
+	}
+
+	// The terms are normalized into their coefficients.
+	let voters = diffs.iter().find(|d| d["name"] == "get_npos_voters").unwrap();
+	let new = &voters["change"]["changed"]["new"];
+	assert_eq!(new["constant"], 23497257000_u64);
+	assert_eq!(new["n * READ"], 4);
+	assert_eq!(new.as_object().unwrap().len(), 7);
+}
+
+#[test]
+fn subweight_compare_files_brief_human_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--format", "brief-human", "--no-color"])
+		.args([
+			"--old",
+			root_dir().join("test_data/old/pallet_staking.rs.txt").to_str().unwrap(),
+			"--new",
+			root_dir().join("test_data/new/pallet_staking.rs.txt").to_str().unwrap(),
+		])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(
+		&out,
+		"pallet_staking.rs.txt::get_npos_voters: 111.28ms -> 144.12ms (+29.51)\n",
+	);
+	assert_not_contains(&out, "| File");
+}
+
+#[test]
+fn subweight_compare_files_chromatic_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--chromatic", "worst"])
+		.args([
+			"--old",
+			root_dir().join("test_data/new/staking_chromatic.rs.txt").to_str().unwrap(),
+			"--new",
+			root_dir().join("test_data/new/staking_chromatic.rs.txt").to_str().unwrap(),
+			"--threshold",
+			"0",
+		])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+	assert_contains(&out, "Time [%]");
+	assert_contains(&out, "Proof [%]");
+	assert_contains(&out, "KiB");
+}
+
+#[test]
+fn subweight_compare_files_over_budget_errors() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base"])
+		.args([
+			"--old",
+			root_dir().join("test_data/new/staking_chromatic.rs.txt").to_str().unwrap(),
+			"--new",
+			root_dir().join("test_data/new/staking_chromatic.rs.txt").to_str().unwrap(),
+			"--max-block-time",
+			"2000000000000",
+			"--max-block-ratio",
+			"0.05",
+		])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(8));
+
+	let out = String::from_utf8_lossy(&output.stderr).trim().to_owned();
+	assert_contains(&out, "2 extrinsics exceed the block budget");
+	assert_contains(&out, "staking_chromatic.rs.txt::get_npos_voters");
+}
+
+#[test]
+fn subweight_compare_files_rename_threshold_errors() {
+	let file = root_dir().join("test_data/new/pallet_staking.rs.txt");
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--detect-renames"])
+		.args(["--old", file.to_str().unwrap(), "--new", file.to_str().unwrap()])
+		.args(["--rename-threshold", "1.5"])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(2));
+	assert_contains(&String::from_utf8_lossy(&output.stderr), "Ratio must be in (0, 1] but is 1.5");
+}
+
+#[test]
+fn subweight_compare_files_fail_on_works() {
+	let cases: &[(&[&str], i32)] = &[
+		(&["changed:30"], 0),
+		(&["changed:20"], 5),
+		(&["removed"], 7),
+		(&["removed,changed:20"], 5),
+		(&["removed", "--fail-on", "changed:20"], 5),
+	];
+
+	for (policies, code) in cases {
+		let output = Command::cargo_bin("subweight")
+			.unwrap()
+			.args(["compare", "files", "--method", "base", "--threshold", "10"])
+			.args([
+				"--old",
+				root_dir().join("test_data/old/pallet_staking.rs.txt").to_str().unwrap(),
+				"--new",
+				root_dir().join("test_data/new/pallet_staking.rs.txt").to_str().unwrap(),
+				"--fail-on",
+			])
+			.args(*policies)
+			.output()
+			.unwrap();
+		assert_eq!(output.status.code(), Some(*code), "Policies {:?}", policies);
+
+		let err = String::from_utf8_lossy(&output.stderr);
+		if *code == 0 {
+			assert!(err.trim().is_empty(), "Unexpected stderr: {}", err);
+		} else {
+			assert_contains(&err, "pallet_staking.rs.txt::");
+		}
+	}
+}
+
+/// Repeatable options must not swallow the positional commits.
+#[test]
+fn subweight_compare_commits_options_parse() {
+	for option in [["--fail-on", "warning"], ["--impl-name", "SubstrateWeight"]] {
+		let output = Command::cargo_bin("subweight")
+			.unwrap()
+			.args(["compare", "commits"])
+			.args(option)
+			.args(["old-ref", "new-ref"])
+			.args(["--method", "base", "--path-pattern", "*.rs", "--repo", "/nonexistent"])
+			.output()
+			.unwrap();
+		assert!(!output.status.success());
+
+		let err = String::from_utf8_lossy(&output.stderr);
+		assert_not_contains(&err, "Unknown policy");
+		assert_contains(&err, "checkout");
+	}
+}
+
+#[test]
+fn subweight_compare_files_print_storage_works() {
+	let old = root_dir().join("test_data/new/staking_chromatic.rs.txt");
+	// Same file name, otherwise they would not be compared with each other.
+	let dir = std::env::temp_dir().join("subweight_print_storage");
+	std::fs::create_dir_all(&dir).unwrap();
+	let new = dir.join("staking_chromatic.rs.txt");
+	let content = std::fs::read_to_string(&old).unwrap().replacen(
+		"Storage: Staking Payee (r:0 w:1)",
+		"Storage: System Account (r:1 w:0)",
+		1,
+	);
+	std::fs::write(&new, content).unwrap();
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0"])
+		.args(["--old", old.to_str().unwrap(), "--new", new.to_str().unwrap()])
+		.args(["--extrinsic", "^bond$", "--print-storage", "--format", "csv"])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(0));
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, ",Storage\n");
+	assert_contains(&out, "-Staking::Payee (r:0 w:1); +System::Account (r:1 w:0)");
+}
+
+#[test]
+fn subweight_compare_files_print_coefficients_works() {
+	let old = root_dir().join("test_data/new/staking_chromatic.rs.txt");
+	// Same file name, otherwise they would not be compared with each other.
+	let dir = std::env::temp_dir().join("subweight_print_coefficients");
+	std::fs::create_dir_all(&dir).unwrap();
+	let new = dir.join("staking_chromatic.rs.txt");
+	let content = std::fs::read_to_string(&old).unwrap().replacen(
+		"reads(4_u64))\n\t\t\t.saturating_add(T::DbWeight::get().writes(4_u64))",
+		"reads(5_u64))\n\t\t\t.saturating_add(T::DbWeight::get().writes(4_u64))",
+		1,
+	);
+	std::fs::write(&new, content).unwrap();
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0", "--no-color"])
+		.args(["--old", old.to_str().unwrap(), "--new", new.to_str().unwrap()])
+		.args(["--extrinsic", "^bond$", "--print-coefficients", "--format", "csv"])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(0));
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, ",Coefficients\n");
+	assert_contains(
+		&out,
+		"constant: 40.60M -> 40.60M (Unchanged); READ: 4 -> 5 (+25.00); WRITE: 4 -> 4 (Unchanged)",
+	);
+}
+
+#[test]
+fn subweight_compare_files_db_weights_works() {
+	let file = root_dir().join("test_data/new/pallet_staking.rs.txt");
+	let db = |name: &str| root_dir().join("test_data/new").join(name);
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0", "--no-color"])
+		.args(["--old", file.to_str().unwrap(), "--new", file.to_str().unwrap()])
+		.args(["--extrinsic", "^bond$", "--format", "csv"])
+		.args(["--old-db-weights", db("rocksdb_weights.rs.txt").to_str().unwrap()])
+		.args(["--new-db-weights", db("paritydb_weights.rs.txt").to_str().unwrap()])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "pallet_staking.rs.txt,bond,41.10us,40.60us,-1.22");
+}
+
+#[test]
+fn subweight_compare_files_detect_renames_works() {
+	let compare = |threshold: &str| {
+		let output = Command::cargo_bin("subweight")
+			.unwrap()
+			.args(["compare", "files", "--method", "base", "--threshold", "0", "--no-color"])
+			.args([
+				"--old",
+				root_dir().join("test_data/old/pallet_staking.rs.txt").to_str().unwrap(),
+				"--new",
+				root_dir().join("test_data/new/pallet_staking.rs.txt").to_str().unwrap(),
+			])
+			.args(["--detect-renames", "--rename-threshold", threshold])
+			.args(["--change", "added", "removed", "renamed"])
+			.output()
+			.unwrap();
+		succeeds(&output);
+		String::from_utf8_lossy(&output.stdout).into_owned()
+	};
+
+	// The removed and added extrinsics are too different to be a rename.
+	let out = compare("0.75");
+	assert_contains(&out, "set_history_depth  | 450.00us |          | Removed");
+	assert_contains(&out, "set_min_commission |          | 104.66us | Added");
+	assert_not_contains(&out, "renamed from");
+
+	let out = compare("0.01");
+	assert_contains(
+		&out,
+		"set_min_commission (renamed from set_history_depth) | 450.00us | 104.66us | -76.74",
+	);
+	assert_not_contains(&out, "Removed");
+	assert_not_contains(&out, "Added");
+}
+
+#[test]
+fn subweight_compare_files_runtime_pattern_works() {
+	// Runtime `a` changes from the old to the new staking weights; `b` stays the same.
+	let dir = std::env::temp_dir().join(format!("subweight-runtimes-{}", std::process::id()));
+	for (revision, runtime, data) in
+		[("old", "a", "old"), ("new", "a", "new"), ("old", "b", "new"), ("new", "b", "new")]
+	{
+		let path = dir.join(revision).join("runtime").join(runtime);
+		std::fs::create_dir_all(&path).unwrap();
+		let src = root_dir().join("test_data").join(data).join("pallet_staking.rs.txt");
+		std::fs::copy(src, path.join("pallet_staking.rs")).unwrap();
+	}
+	let files = |revision: &str| {
+		["a", "b"].map(|r| {
+			dir.join(revision)
+				.join("runtime")
+				.join(r)
+				.join("pallet_staking.rs")
+				.display()
+				.to_string()
+		})
+	};
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "10"])
+		.arg("--old")
+		.args(files("old"))
+		.arg("--new")
+		.args(files("new"))
+		.args(["--runtime-pattern", "runtime/([^/]+)/", "--format", "csv"])
+		.output()
+		.unwrap();
+	std::fs::remove_dir_all(&dir).unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "File,Extrinsic,a,b");
+	assert_contains(&out, "pallet_staking.rs,get_npos_voters,+29.51,Unchanged");
+	assert_contains(&out, "pallet_staking.rs,set_history_depth,Removed,-");
+	assert_not_contains(&out, "pallet_staking.rs,bond,");
+	// No colors even without `--no-color`.
+	assert_not_contains(&out, "\x1b[");
+}
+
+#[test]
+fn subweight_compare_runtimes_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "runtimes", "--method", "base", "--threshold", "0", "--no-color"])
+		.args(["--repo", root_dir().to_str().unwrap()])
+		.args(["--left", "test_data/old", "--right", "test_data/new"])
+		.args(["--path-pattern", "pallet_*.rs.txt", "--format", "csv"])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "pallet_staking.rs.txt,bond,559.92us,540.60us,-3.45");
+	assert_contains(&out, "pallet_staking.rs.txt,set_min_commission,,104.66us,Added");
+}
+
+#[test]
+fn subweight_compare_files_overhead_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0", "--format", "csv"])
+		.arg("--no-color")
+		.args([
+			"--old",
+			root_dir().join("test_data/new/extrinsic_weights.rs.txt").to_str().unwrap(),
+			root_dir().join("test_data/new/rocksdb_weights.rs.txt").to_str().unwrap(),
+			"--new",
+			root_dir()
+				.join("test_data/chromatic/extrinsic_weights.rs.txt")
+				.to_str()
+				.unwrap(),
+			root_dir().join("test_data/new/paritydb_weights.rs.txt").to_str().unwrap(),
+		])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "extrinsic_weights.rs.txt,ExtrinsicBaseWeight,85.21us,99.84us,+17.17");
+	assert_contains(&out, "rocksdb_weights.rs.txt,read,25.00ns,,Removed");
+	assert_contains(&out, "paritydb_weights.rs.txt,write,,50ps,Added");
+}
+
+#[test]
+fn subweight_compare_files_extrinsic_base_works() {
+	let file = root_dir().join("test_data/new/pallet_staking.rs.txt");
+	let base = |dir: &str| root_dir().join("test_data").join(dir).join("extrinsic_weights.rs.txt");
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0", "--no-color"])
+		.args(["--old", file.to_str().unwrap(), "--new", file.to_str().unwrap()])
+		.args(["--extrinsic", "^bond$", "--format", "csv"])
+		.args(["--old-extrinsic-base", base("new").to_str().unwrap()])
+		.args(["--new-extrinsic-base", base("chromatic").to_str().unwrap()])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "pallet_staking.rs.txt,bond,625.81us,640.44us,+2.34");
+}
+
+#[test]
+fn subweight_compare_files_print_summary_works() {
+	let old = root_dir().join("test_data/new/staking_chromatic.rs.txt");
+	// Same file name, otherwise they would not be compared with each other.
+	let dir = std::env::temp_dir().join("subweight_print_summary");
+	std::fs::create_dir_all(&dir).unwrap();
+	let new = dir.join("staking_chromatic.rs.txt");
+	let content = std::fs::read_to_string(&old).unwrap().replacen(
+		"Minimum execution time: 40_015 nanoseconds.",
+		"Minimum execution time: 80_030 nanoseconds.",
+		1,
+	);
+	std::fs::write(&new, content).unwrap();
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0"])
+		.args(["--old", old.to_str().unwrap(), "--new", new.to_str().unwrap()])
+		.args(["--extrinsic", "^bond$", "--print-summary", "--format", "csv", "--no-color"])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(0));
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "Old Min Time,New Min Time,Min Time Change Percent,Old Measured Proof");
+	assert_contains(&out, "40.02us,80.03us,+100.00,1.05KiB,1.05KiB,Unchanged");
+	assert_contains(&out, "Estimated Proof Change Percent,Proof Estimation Percent");
+	// Estimated 10386 byte vs. measured 1079 byte.
+	assert_contains(&out, ",Unchanged,+862.56");
+}
+
+#[test]
+fn subweight_compare_files_metadata_warns() {
+	let old = root_dir().join("test_data/new/staking_chromatic.rs.txt");
+	// Same file name, otherwise they would not be compared with each other.
+	let dir = std::env::temp_dir().join("subweight_metadata_warns");
+	std::fs::create_dir_all(&dir).unwrap();
+	let new = dir.join("staking_chromatic.rs.txt");
+	let content = std::fs::read_to_string(&old)
+		.unwrap()
+		.replacen("CPU: `Intel(R) Core(TM) i7-7700K CPU @ 4.20GHz`", "CPU: `AMD EPYC 7B13`", 1)
+		.replacen("REPEAT: `20`", "REPEAT: `5`", 1);
+	std::fs::write(&new, content).unwrap();
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0"])
+		.args(["--old", old.to_str().unwrap(), "--new", new.to_str().unwrap()])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(0));
+
+	let err = String::from_utf8_lossy(&output.stderr);
+	assert_eq!(
+		err.trim(),
+		"Warning: staking_chromatic.rs.txt: Different CPU: `Intel(R) Core(TM) i7-7700K CPU @ \
+		 4.20GHz` vs `AMD EPYC 7B13`\nWarning: staking_chromatic.rs.txt: Fewer repeats: 20 vs 5"
+	);
+
+	// Reported once for the whole file and not for each of its extrinsics.
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["compare", "files", "--method", "base", "--threshold", "0"])
+		.args(["--old", old.to_str().unwrap(), "--new", new.to_str().unwrap()])
+		.args(["--fail-on", "warning"])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(4));
+	let err = String::from_utf8_lossy(&output.stderr);
+	assert_contains(&err, "Policy 'warning' is violated by 1 files:\n  staking_chromatic.rs.txt (");
+	assert_not_contains(&err, "extrinsics:");
+}
+
+#[test]
+fn subweight_parse_errors_show_source() {
+	let dir = std::env::temp_dir().join("subweight_parse_errors_show_source");
+	std::fs::create_dir_all(&dir).unwrap();
+	let file = dir.join("weights.rs");
+	std::fs::write(
+		&file,
+		"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {\n\tfn foo() -> Weight \
+		 {\n\t\tWeight::from_ref_time(5 as u64)\n\t\t\t.frobnicate(2)\n\t}\n}\n",
+	)
+	.unwrap();
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["parse", "files", file.to_str().unwrap()])
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(1));
+
+	let err = String::from_utf8_lossy(&output.stderr);
+	assert_eq!(
+		err.trim_end(),
+		format!(
+			"error: Unknown kind of weight file\n --> {0}\n  = pallet: Unknown call: frobnicate\n     \
+			 --> {0}:4:5\n      |\n    4 | \t\t\t.frobnicate(2)\n      | \t\t\t ^^^^^^^^^^\n  = \
+			 storage: No DB weights found\n  = overhead: No Overhead weights found",
+			file.display()
+		)
+	);
+}
+
+/// The files are positional; clap rejects a positional argument with a `long` flag.
+#[test]
+fn subweight_parse_files_help_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["parse", "files", "--help"])
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert!(
+		out.starts_with("Parses weight files and reports their kind and extrinsics"),
+		"{}",
+		out
+	);
+	assert_contains(&out, "Usage: subweight parse files [OPTIONS] [FILES]...");
+	assert_not_contains(&out, "--files");
+}
+
+#[test]
+fn subweight_parse_files_reports_works() {
+	let file = |name: &str| root_dir().join("test_data").join(name);
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["parse", "files"])
+		.arg(file("new/pallet_staking.rs.txt"))
+		.arg(file("new/rocksdb_weights.rs.txt"))
+		.arg(file("chromatic/block_weights.rs.txt"))
+		.arg(file("missing.rs.txt"))
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(1));
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	let rows = out
+		.lines()
+		.filter(|l| l.starts_with("| /"))
+		.map(|l| l.split('|').map(str::trim).skip(2).take(2).collect::<Vec<_>>().join(" "))
+		.collect::<Vec<_>>();
+	assert_eq!(rows, vec!["pallet 30", "storage 2", "overhead 1", " 0"]);
+	assert_contains(&out, "Parsed 3 of 4 files successfully");
+	assert_contains(&String::from_utf8_lossy(&output.stderr), "missing.rs.txt");
+}
+
+#[test]
+fn subweight_parse_files_json_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["parse", "files", "--format", "json"])
+		.arg(root_dir().join("test_data/new/pallet_staking.rs.txt"))
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let extrinsics = json.as_array().unwrap();
+	assert_eq!(extrinsics.len(), 30);
+	let ext = extrinsics.iter().find(|e| e["name"] == "withdraw_unbonded_update").unwrap();
+	assert_eq!(ext["pallet"], "pallet_staking.rs.txt");
+	assert_eq!(ext["comp_ranges"]["s"], serde_json::json!({ "min": 0, "max": 100 }));
+	assert_eq!(ext["base"], serde_json::json!({ "time": 432410035_u64, "proof": 10442 }));
+	assert_eq!(ext["worst"], serde_json::json!({ "time": 433319035_u64, "proof": 10442 }));
+	assert!(ext["term"]["Add"].is_array());
+	assert_eq!(ext["lints"], serde_json::json!([]));
+	assert_eq!(ext["error"], serde_json::Value::Null);
+}
+
+/// A term that cannot be evaluated is dumped with the reason instead of its weights.
+#[test]
+fn subweight_parse_files_json_eval_error_works() {
+	let dir = std::env::temp_dir().join("subweight_parse_files_json_eval_error_works");
+	std::fs::create_dir_all(&dir).unwrap();
+	let file = dir.join("weights.rs");
+	std::fs::write(
+		&file,
+		"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {\n\tfn foo(n: u32, ) -> \
+		 Weight {\n\t\t(5 as Weight).saturating_add((3 as Weight).saturating_mul(n as \
+		 Weight))\n\t}\n}\n",
+	)
+	.unwrap();
+
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["parse", "files", "--format", "json"])
+		.arg(&file)
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(json[0]["worst"], serde_json::Value::Null);
+	assert_eq!(json[0]["error"]["kind"], "missing-range");
+	assert_eq!(json[0]["error"]["component"], "n");
+}
+
+#[test]
+fn subweight_parse_files_yaml_works() {
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["parse", "files", "--format", "yaml"])
+		.arg(root_dir().join("test_data/new/rocksdb_weights.rs.txt"))
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "- pallet: rocksdb_weights.rs.txt\n  name: read\n");
+	assert_contains(&out, "  name: write\n");
+	assert_not_contains(&out, "Parsed 1 of 1 files successfully");
+}
+
+#[test]
+fn subweight_parse_files_impl_name_works() {
+	let dir = std::env::temp_dir().join("subweight_parse_files_impl_name_works");
+	std::fs::create_dir_all(&dir).unwrap();
+	let file = dir.join("weights.rs");
+	std::fs::write(
+		&file,
+		"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {\n\tfn foo() -> Weight \
+		 {\n\t\t5 as Weight\n\t}\n}\nimpl WeightInfo for () {\n\tfn foo() -> Weight {\n\t\t7 as \
+		 Weight\n\t}\n}\n",
+	)
+	.unwrap();
+
+	let time = |args: &[&str]| {
+		let output = Command::cargo_bin("subweight")
+			.unwrap()
+			.args(["parse", "files", "--format", "json"])
+			.arg(&file)
+			.args(args)
+			.output()
+			.unwrap();
+		succeeds(&output);
+		let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+		json[0]["base"]["time"].as_u64().unwrap()
+	};
+	assert_eq!(time(&[]), 5);
+	assert_eq!(time(&["--impl-name", "()"]), 7);
+	assert_eq!(time(&["--impl-name", "Missing,(),SubstrateWeight"]), 7);
+	assert_eq!(time(&["--impl-name", "Missing", "--impl-name", "()"]), 7);
+}
+
+#[test]
+fn subweight_lint_works() {
+	let file = root_dir().join("test_data/old/pallet_staking.rs.txt");
+	let lint = |args: &[&str]| {
+		Command::cargo_bin("subweight")
+			.unwrap()
+			.arg("lint")
+			.arg(&file)
+			.args(args)
+			.output()
+			.unwrap()
+	};
+
+	// Only warnings: success.
+	let output = lint(&[]);
+	succeeds(&output);
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_contains(&out, "warning[missing-proof]: The weight has no proof size");
+	assert_contains(&out, "warning[missing-range]: Component 's' has no range annotation");
+	assert_contains(&out, "  = extrinsic: withdraw_unbonded_update");
+	assert_contains(&out, "Found 0 errors, 48 warnings and 0 notes");
+
+	let output =
+		lint(&["--allow", "missing-proof", "--allow", "missing-range:.*::withdraw_unbonded_.*"]);
+	let out = String::from_utf8_lossy(&output.stdout);
+	assert_not_contains(&out, "missing-proof");
+	assert_not_contains(&out, "= extrinsic: withdraw_unbonded_update");
+	assert_contains(&out, "Found 0 errors, 16 warnings and 0 notes");
+
+	// Invalid regexes are rejected right away.
+	let output = lint(&["--allow", "missing-range:("]);
+	assert_eq!(output.status.code(), Some(2));
+	assert_contains(&String::from_utf8_lossy(&output.stderr), "Invalid allow regex");
+
+	// The same file twice has duplicate extrinsics which is an error.
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["lint", "--format", "json"])
+		.arg(&file)
+		.arg(&file)
+		.output()
+		.unwrap();
+	assert_eq!(output.status.code(), Some(1));
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	let duplicate = json
+		.as_array()
+		.unwrap()
+		.iter()
+		.find(|f| f["rule"] == "duplicate-extrinsic")
+		.unwrap();
+	assert_eq!(duplicate["severity"], "error");
+	assert_eq!(duplicate["extrinsic"], "bond");
+
+	// The same pallet in different directories is fine.
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["lint", "--format", "json"])
+		.arg(&file)
+		.arg(root_dir().join("test_data/new/pallet_staking.rs.txt"))
+		.output()
+		.unwrap();
+	let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert!(!json.as_array().unwrap().iter().any(|f| f["rule"] == "duplicate-extrinsic"));
+}
+
+#[test]
+fn subweight_lint_sarif_works() {
+	let file = root_dir().join("test_data/old/pallet_staking.rs.txt");
+	let output = Command::cargo_bin("subweight")
+		.unwrap()
+		.args(["lint", "--format", "sarif"])
+		.arg(&file)
+		.output()
+		.unwrap();
+	succeeds(&output);
+
+	let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+	assert_eq!(sarif["version"], "2.1.0");
+	let run = &sarif["runs"][0];
+	assert_eq!(run["tool"]["driver"]["name"], "subweight");
+	assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 12);
+	let result = &run["results"][0];
+	assert_eq!(result["ruleId"], "missing-proof");
+	assert_eq!(result["level"], "warning");
+	let location = &result["locations"][0]["physicalLocation"];
+	assert_eq!(location["artifactLocation"]["uri"], file.display().to_string());
+	assert!(location["region"]["startLine"].as_u64().unwrap() > 0);
+}
@@ -4,18 +4,18 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughpu
 use std::path::Path;
 
 use subweight_core::parse::{
-	pallet::parse_file as parse_pallet, storage::parse_file as parse_storage,
+	pallet::parse_pallet_file as parse_pallet, storage::parse_file as parse_storage,
 };
 
 fn bench_parse_pallet(c: &mut Criterion) {
 	let path = Path::new("../test_data/new/pallet_staking.rs.txt");
-	let num_ext = parse_pallet(path).expect("Must work").len();
+	let num_ext = parse_pallet(path, &Default::default()).expect("Must work").len();
 	let mut group = c.benchmark_group("Parse");
 
 	group.sample_size(100);
 	group.throughput(Throughput::Elements(num_ext as u64));
 	group.bench_function("Pallet.Extrinsic", |b| {
-		b.iter(|| parse_pallet(black_box(path)).expect("Must work"))
+		b.iter(|| parse_pallet(black_box(path), &Default::default()).expect("Must work"))
 	});
 }
 
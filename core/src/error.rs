@@ -0,0 +1,153 @@
+//! Error type of the library.
+
+use std::path::Path;
+
+use crate::parse::pallet::WeightKind;
+
+/// Everything that can go wrong while parsing or comparing weight files.
+///
+/// Serializes with a `kind` tag in kebab-case, for example:
+/// `{"kind": "missing-range", "component": "n", "extrinsic": "pallet_staking::bond"}`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Error {
+	/// The content of a file could not be parsed.
+	Parse {
+		/// The file that could not be parsed, if known.
+		file: Option<String>,
+		/// Location of the offending code in the file, if known.
+		span: Option<Span>,
+		msg: String,
+	},
+	/// A weight expression calls a function that is not understood.
+	UnknownCall { name: String, file: Option<String>, span: Option<Span> },
+	/// A file is none of the known kinds of weight files.
+	UnknownFile {
+		file: String,
+		/// Why each parser rejected the file.
+		rejections: Vec<Rejection>,
+	},
+	/// A file could not be read.
+	Io { file: String, msg: String },
+	/// A git command failed.
+	Git { msg: String },
+	/// A component has no range but the compare method needs one.
+	MissingRange { component: String, extrinsic: String },
+	/// A component has different ranges in the old and new version but the compare method needs
+	/// them to be equal.
+	DifferentRanges { component: String, extrinsic: String },
+	/// A term could not be evaluated.
+	Eval { msg: String },
+	/// A parameter like a path pattern or regex is invalid.
+	InvalidParam { msg: String },
+}
+
+/// Why a parser rejected a file, see [`Error::UnknownFile`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Rejection {
+	/// The kind of file that the parser expected.
+	pub parser: WeightKind,
+	pub error: Error,
+}
+
+/// Location of some code in a file.
+///
+/// Lines start at 1 and columns at 0, just like in [`proc_macro2::LineColumn`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Span {
+	pub line: usize,
+	pub column: usize,
+	pub end_line: usize,
+	pub end_column: usize,
+}
+
+impl From<proc_macro2::Span> for Span {
+	fn from(span: proc_macro2::Span) -> Self {
+		let (start, end) = (span.start(), span.end());
+		Self { line: start.line, column: start.column, end_line: end.line, end_column: end.column }
+	}
+}
+
+impl Error {
+	/// A [`Self::Parse`] error without a location.
+	pub fn parse(msg: impl Into<String>) -> Self {
+		Self::Parse { file: None, span: None, msg: msg.into() }
+	}
+
+	/// A [`Self::Parse`] error at the given location.
+	pub fn at(span: impl Into<Span>, msg: impl Into<String>) -> Self {
+		Self::Parse { file: None, span: Some(span.into()), msg: msg.into() }
+	}
+
+	/// Sets the file of a parse error if it is not set yet.
+	pub fn in_file(mut self, path: &Path) -> Self {
+		if let Self::Parse { file, .. } | Self::UnknownCall { file, .. } = &mut self {
+			file.get_or_insert_with(|| path.display().to_string());
+		}
+		self
+	}
+
+	/// Sets the location of a parse error if it is not set yet.
+	pub fn with_span(mut self, span: impl Into<Span>) -> Self {
+		if let Self::Parse { span: s, .. } | Self::UnknownCall { span: s, .. } = &mut self {
+			s.get_or_insert_with(|| span.into());
+		}
+		self
+	}
+
+	/// The file and location of a parse error.
+	pub fn location(&self) -> Option<(&str, Option<Span>)> {
+		match self {
+			Self::Parse { file: Some(file), span, .. } |
+			Self::UnknownCall { file: Some(file), span, .. } => Some((file, *span)),
+			_ => None,
+		}
+	}
+
+	/// The error without its location.
+	pub fn message(&self) -> String {
+		match self {
+			Self::Parse { msg, .. } => msg.clone(),
+			Self::UnknownCall { name, .. } => format!("Unknown call: {}", name),
+			Self::UnknownFile { .. } => "Unknown kind of weight file".into(),
+			e => e.to_string(),
+		}
+	}
+}
+
+impl core::fmt::Display for Error {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Parse { .. } | Self::UnknownCall { .. } => match self.location() {
+				Some((file, Some(span))) =>
+					write!(f, "{}:{}:{}: {}", file, span.line, span.column + 1, self.message()),
+				Some((file, None)) => write!(f, "{}: {}", file, self.message()),
+				None => write!(f, "{}", self.message()),
+			},
+			Self::UnknownFile { file, rejections } => {
+				write!(f, "{}: {}", file, self.message())?;
+				for r in rejections {
+					write!(f, "; {}: {}", r.parser, r.error.message())?;
+				}
+				Ok(())
+			},
+			Self::Io { file, msg } => write!(f, "{}: {}", file, msg),
+			Self::Git { msg } => write!(f, "{}", msg),
+			Self::MissingRange { component, extrinsic } => write!(
+				f,
+				"No range for component {} of call {} - use Guess instead!",
+				component, extrinsic
+			),
+			Self::DifferentRanges { component, extrinsic } => write!(
+				f,
+				"Component {} of call {} has different ranges in the old and new version - use \
+				 Guess instead!",
+				component, extrinsic
+			),
+			Self::Eval { msg } => write!(f, "{}", msg),
+			Self::InvalidParam { msg } => write!(f, "{}", msg),
+		}
+	}
+}
+
+impl std::error::Error for Error {}
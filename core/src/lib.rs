@@ -9,27 +9,39 @@ use lazy_static::lazy_static;
 
 use std::{
 	cmp::Ordering,
-	collections::{BTreeSet, HashMap, HashSet},
+	collections::{BTreeMap, BTreeSet, HashMap},
 	path::{Path, PathBuf},
 	process::Command,
 };
 use syn::{Expr, Item, Type};
 
+pub mod error;
+pub mod lint;
 pub mod parse;
+pub mod rename;
+pub mod runtime;
 pub mod scope;
 pub mod term;
 pub mod testing;
 pub mod traits;
+pub mod worst;
 
 #[cfg(test)]
 mod test;
 
-use parse::pallet::{
-	parse_files_in_repo, try_parse_files_in_repo, ChromaticExtrinsic, ComponentRange,
-	SimpleExtrinsic,
+pub use error::Error;
+
+use parse::{
+	pallet::{
+		BenchmarkSummary, ChromaticExtrinsic, ComponentRange, GenericExtrinsic, ImplParams,
+		SimpleExtrinsic, StorageAccess, WeightKind,
+	},
+	parse_files_in_repo, try_parse_files_in_repo,
 };
+use rename::{Rename, RenameParams};
 use scope::SimpleScope;
-use term::SimpleTerm;
+use term::{Monomial, SimpleTerm};
+use worst::{ComponentBounds, Objective};
 
 lazy_static! {
 	/// Version of the library. Example: `swc 0.2.0+78a04b2`.
@@ -47,54 +59,253 @@ pub type TotalDiff = Vec<ExtrinsicDiff>;
 pub type Percent = f64;
 pub const WEIGHT_PER_NANOS: u128 = 1_000;
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize)]
 #[cfg_attr(feature = "bloat", derive(Debug))]
 pub struct ExtrinsicDiff {
 	pub name: ExtrinsicName,
 	pub file: String,
 
 	pub change: TermDiff,
+	/// The change per dimension when comparing both dimensions at once.
+	///
+	/// [`Self::change`] is then the one that was selected by [`CompareParams::chromatic`].
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub chromatic: Option<ChromaticDiff>,
+	/// Storage items that are accessed differently according to the doc comments.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub storage: Vec<StorageChange>,
+	/// Values from the benchmark comments; `None` if neither version has them.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub summary: Option<SummaryDiff>,
+	/// The change of each coefficient of the weight term. See [`compare_coefficients`].
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub coefficients: Vec<CoefficientChange>,
+	/// The removed extrinsic that this one replaces if it was renamed or moved.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub renamed_from: Option<Rename>,
+	/// Why the old and new weights of the file should not be compared. See [`compare_metadata`].
+	///
+	/// This is the same for all extrinsics of a file; use [`metadata_mismatches`] to get it once
+	/// per file.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub metadata: Vec<String>,
+}
+
+/// The [`BenchmarkSummary`] of the old and new version of an extrinsic.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct SummaryDiff {
+	pub old: Option<BenchmarkSummary>,
+	pub new: Option<BenchmarkSummary>,
+}
+
+impl SummaryDiff {
+	pub fn new<T>(
+		old: Option<&GenericExtrinsic<T>>,
+		new: Option<&GenericExtrinsic<T>>,
+	) -> Option<Self> {
+		let old = old.map(|o| o.summary.clone()).filter(|s| !s.is_empty());
+		let new = new.map(|n| n.summary.clone()).filter(|s| !s.is_empty());
+		(old.is_some() || new.is_some()).then_some(Self { old, new })
+	}
+
+	/// Compares one value of the summaries, for example the `min_time`.
+	///
+	/// Returns `None` if neither summary has the value.
+	pub fn compare(
+		&self,
+		value: impl Fn(&BenchmarkSummary) -> Option<u128>,
+	) -> Option<ValueChange> {
+		ValueChange::new(self.old.as_ref().and_then(&value), self.new.as_ref().and_then(&value))
+	}
+
+	/// Compares the measured with the estimated proof size of the new summary, or of the old one if
+	/// the extrinsic was removed. See [`BenchmarkSummary::proof_estimation`].
+	pub fn proof_estimation(&self) -> Option<ValueChange> {
+		self.new
+			.as_ref()
+			.or(self.old.as_ref())
+			.and_then(BenchmarkSummary::proof_estimation)
+	}
+}
+
+/// The change of a plain value that was read from a weight file.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct ValueChange {
+	pub old: Option<u128>,
+	pub new: Option<u128>,
+	pub change: RelativeChange,
+	pub percent: Percent,
+}
+
+impl ValueChange {
+	pub fn new(old: Option<u128>, new: Option<u128>) -> Option<Self> {
+		if old.is_none() && new.is_none() {
+			return None
+		}
+		let change =
+			if old == new { RelativeChange::Unchanged } else { RelativeChange::new(old, new) };
+		let percent = percent(old.unwrap_or_default(), new.unwrap_or_default());
+
+		Some(Self { old, new, change, percent })
+	}
+}
+
+/// The change of the coefficient of one monomial of a weight term.
+///
+/// Shows whether the base weight or the slope of a component changed.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct CoefficientChange {
+	pub monomial: Monomial,
+	#[serde(flatten)]
+	pub change: ValueChange,
 }
 
-#[derive(Clone)]
+/// A storage item that is accessed differently by the old and new version of an extrinsic.
+///
+/// Either `old` or `new` is `None` if the item was only accessed by one of them.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct StorageChange {
+	pub pallet: String,
+	pub item: String,
+	pub old: Option<StorageAccess>,
+	pub new: Option<StorageAccess>,
+}
+
+impl StorageChange {
+	pub fn change(&self) -> RelativeChange {
+		match (&self.old, &self.new) {
+			(None, _) => RelativeChange::Added,
+			(_, None) => RelativeChange::Removed,
+			(Some(old), Some(new)) if old == new => RelativeChange::Unchanged,
+			_ => RelativeChange::Changed,
+		}
+	}
+}
+
+/// Formats the change in the form `+System::Account (r:1 w:0)` for added, `-` for removed and
+/// `~Staking::Ledger (r:1 w:1->2)` for changed items.
+impl core::fmt::Display for StorageChange {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		let name = if self.pallet.is_empty() {
+			self.item.clone()
+		} else {
+			format!("{}::{}", self.pallet, self.item)
+		};
+		let access = |a: &StorageAccess| format!("r:{} w:{}", a.reads, a.writes);
+
+		match (&self.old, &self.new) {
+			(None, Some(new)) => write!(f, "+{} ({})", name, access(new)),
+			(Some(old), None) => write!(f, "-{} ({})", name, access(old)),
+			(Some(old), Some(new)) => {
+				let mut diffs = Vec::new();
+				let mut push = |what: &str, old: String, new: String| {
+					if old == new {
+						diffs.push(format!("{}:{}", what, new));
+					} else {
+						diffs.push(format!("{}:{}->{}", what, old, new));
+					}
+				};
+				push("r", old.reads.to_string(), new.reads.to_string());
+				push("w", old.writes.to_string(), new.writes.to_string());
+				if old.max_size != new.max_size {
+					let fmt = |s: Option<u32>| s.map_or("None".into(), |s| s.to_string());
+					push("max_size", fmt(old.max_size), fmt(new.max_size));
+				}
+				write!(f, "~{} ({})", name, diffs.join(" "))
+			},
+			(None, None) => write!(f, "{}", name),
+		}
+	}
+}
+
+/// The changes of an extrinsic in both dimensions.
+#[derive(Clone, serde::Serialize)]
+#[cfg_attr(feature = "bloat", derive(Debug))]
+pub struct ChromaticDiff {
+	pub time: TermDiff,
+	pub proof: TermDiff,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
 #[cfg_attr(feature = "bloat", derive(Debug))]
 pub enum TermDiff {
 	Changed(TermChange),
-	Warning(TermChange, String),
-	Failed(String),
+	/// The change of an extrinsic that failed a sanity check.
+	Warning {
+		change: TermChange,
+		warnings: Vec<String>,
+	},
+	/// The worst case weight exceeds the block budget of [`BudgetParams`].
+	///
+	/// Also has the warnings that the extrinsic would otherwise have as [`Self::Warning`].
+	OverBudget {
+		change: TermChange,
+		message: String,
+		warnings: Vec<String>,
+	},
+	Failed(Error),
 }
 
 impl ExtrinsicDiff {
 	pub fn term(&self) -> Option<&TermChange> {
-		match &self.change {
-			TermDiff::Changed(change) => Some(change),
-			TermDiff::Warning(change, _) => Some(change),
-			_ => None,
-		}
+		self.change.term()
 	}
 
-	pub fn error(&self) -> Option<&String> {
+	pub fn error(&self) -> Option<&Error> {
 		match &self.change {
 			TermDiff::Failed(err) => Some(err),
 			_ => None,
 		}
 	}
 
-	pub fn warning(&self) -> Option<&String> {
-		match &self.change {
-			TermDiff::Warning(_, warning) => Some(warning),
-			_ => None,
+	/// The warnings of the extrinsic, also if it is over budget; of both dimensions if it is
+	/// chromatic.
+	pub fn warnings(&self) -> Vec<&String> {
+		self.diffs()
+			.into_iter()
+			.flat_map(|diff| match diff {
+				TermDiff::Warning { warnings, .. } | TermDiff::OverBudget { warnings, .. } =>
+					warnings.iter().collect(),
+				_ => Vec::new(),
+			})
+			.collect()
+	}
+
+	/// The block budget violations of the extrinsic; of both dimensions if it is chromatic.
+	pub fn over_budget(&self) -> Vec<&String> {
+		self.diffs()
+			.into_iter()
+			.filter_map(|diff| match diff {
+				TermDiff::OverBudget { message, .. } => Some(message),
+				_ => None,
+			})
+			.collect()
+	}
+
+	/// Whether the extrinsic could not be compared; in any dimension if it is chromatic.
+	pub fn failed(&self) -> bool {
+		self.diffs().into_iter().any(|diff| matches!(diff, TermDiff::Failed(_)))
+	}
+
+	/// The change of each dimension if it is chromatic, otherwise only [`Self::change`].
+	fn diffs(&self) -> Vec<&TermDiff> {
+		match &self.chromatic {
+			Some(chromatic) => vec![&chromatic.time, &chromatic.proof],
+			None => vec![&self.change],
 		}
 	}
 }
 
 // Uses options since extrinsics can be added or removed and any time.
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize)]
 #[cfg_attr(feature = "bloat", derive(Debug))]
 pub struct TermChange {
+	#[serde(serialize_with = "serialize_term")]
 	pub old: Option<SimpleTerm>,
 	pub old_v: Option<u128>,
 
+	#[serde(serialize_with = "serialize_term")]
 	pub new: Option<SimpleTerm>,
 	pub new_v: Option<u128>,
 
@@ -102,11 +313,64 @@ pub struct TermChange {
 	pub percent: Percent,
 	pub change: RelativeChange,
 	pub method: CompareMethod,
+	/// The largest absolute difference if it is at other components than the relative one.
+	///
+	/// Only searched for methods that evaluate a range of components like
+	/// [`CompareMethod::ExactWorst`].
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub absolute: Option<AbsoluteChange>,
+}
+
+/// Serializes a term as the coefficient of each monomial, like `{"constant": 5, "n * READ": 2}`.
+///
+/// Unlike the syntax tree it was parsed from, this does not depend on how the term was written.
+/// A term that cannot be normalized is serialized as `null`.
+fn serialize_term<S: serde::Serializer>(
+	term: &Option<SimpleTerm>,
+	serializer: S,
+) -> Result<S::Ok, S::Error> {
+	match term.as_ref().and_then(SimpleTerm::coefficients) {
+		Some(coefficients) => serializer.collect_map(coefficients),
+		None => serializer.serialize_none(),
+	}
+}
+
+/// The components at which the weight changed the most in absolute terms.
+#[derive(Clone, serde::Serialize)]
+#[cfg_attr(feature = "bloat", derive(Debug))]
+pub struct AbsoluteChange {
+	pub scope: SimpleScope,
+	pub old_v: u128,
+	pub new_v: u128,
+}
+
+impl AbsoluteChange {
+	/// Returns `None` if the change is at the same components as `relative`.
+	fn new(relative: &TermChange, absolute: TermChange) -> Option<Self> {
+		if absolute.scope == relative.scope {
+			return None
+		}
+		Some(Self { scope: absolute.scope, old_v: absolute.old_v?, new_v: absolute.new_v? })
+	}
+
+	/// The absolute difference `new_v - old_v`.
+	pub fn diff(&self) -> i128 {
+		self.new_v as i128 - self.old_v as i128
+	}
 }
 
 // TODO rename
 #[derive(
-	Debug, serde::Deserialize, clap::ValueEnum, Clone, Eq, Ord, PartialEq, PartialOrd, Copy,
+	Debug,
+	serde::Serialize,
+	serde::Deserialize,
+	clap::ValueEnum,
+	Clone,
+	Eq,
+	Ord,
+	PartialEq,
+	PartialOrd,
+	Copy,
 )]
 #[serde(rename_all = "kebab-case")]
 pub enum RelativeChange {
@@ -114,10 +378,12 @@ pub enum RelativeChange {
 	Added,
 	Removed,
 	Changed,
+	/// Renamed or moved to another pallet; still has the weight change. See [`rename`].
+	Renamed,
 }
 
 /// Parameters for modifying the benchmark behaviour.
-#[derive(Debug, Clone, PartialEq, Eq, Args)]
+#[derive(Debug, Clone, PartialEq, Args)]
 pub struct CompareParams {
 	#[clap(long, short, value_name = "METHOD", ignore_case = true)]
 	pub method: CompareMethod,
@@ -125,6 +391,32 @@ pub struct CompareParams {
 	#[clap(long, short, value_name = "UNIT", ignore_case = true, default_value = "time")]
 	pub unit: Dimension,
 
+	/// Compare both dimensions (time and proof) at once.
+	///
+	/// Selects the dimension that the filtering and sorting acts on. Overrides `--unit`.
+	#[clap(long, value_name = "DIMENSION", ignore_case = true)]
+	pub chromatic: Option<DimensionSelector>,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub budget: BudgetParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub db: DbParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub overhead: OverheadParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub impls: ImplParams,
+
+	#[allow(missing_docs)]
+	#[clap(flatten)]
+	pub renames: RenameParams,
+
 	#[clap(long)]
 	pub ignore_errors: bool,
 
@@ -145,6 +437,26 @@ pub struct CompareParams {
 	pub offline: bool,
 }
 
+/// Parameters for checking extrinsics against the maximal block weight.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct BudgetParams {
+	/// Maximal ref-time of a block in pico seconds.
+	///
+	/// Enables the block budget check for the time dimension.
+	#[clap(long, value_name = "PICOS")]
+	pub max_block_time: Option<u128>,
+
+	/// Maximal proof size of a block in bytes.
+	///
+	/// Enables the block budget check for the proof dimension.
+	#[clap(long, value_name = "BYTES")]
+	pub max_block_proof: Option<u128>,
+
+	/// Fraction of the maximal block weight that a single extrinsic may consume.
+	#[clap(long, value_name = "RATIO", default_value = "0.75", value_parser = parse_ratio)]
+	pub max_block_ratio: f64,
+}
+
 #[derive(Debug, Clone, PartialEq, Args)]
 #[cfg_attr(feature = "bloat", derive(Default))]
 pub struct FilterParams {
@@ -163,10 +475,267 @@ pub struct FilterParams {
 	pub pallet: Option<String>,
 }
 
+/// Parameters for the weight of a storage read and write.
+///
+/// They default to the RocksDB weights of Substrate. Each revision can have its own weights, so a
+/// change of them shows up in the cost of the extrinsics.
+#[derive(Debug, Clone, PartialEq, Args, Default)]
+pub struct DbParams {
+	/// DB weight file of both revisions, for example `rocksdb_weights.rs`.
+	///
+	/// Relative to the repository when comparing commits; it is then read at each revision.
+	#[clap(long, value_name = "FILE")]
+	pub db_weights: Option<PathBuf>,
+
+	/// DB weight file of the old revision. Overrides `--db-weights`.
+	#[clap(long, value_name = "FILE")]
+	pub old_db_weights: Option<PathBuf>,
+
+	/// DB weight file of the new revision. Overrides `--db-weights`.
+	#[clap(long, value_name = "FILE")]
+	pub new_db_weights: Option<PathBuf>,
+
+	/// Weight of a storage read in pico seconds. Overrides the DB weight files.
+	#[clap(long, value_name = "PICOS")]
+	pub read: Option<u128>,
+
+	/// Weight of a storage write in pico seconds. Overrides the DB weight files.
+	#[clap(long, value_name = "PICOS")]
+	pub write: Option<u128>,
+
+	/// The DB weights of the old revision as loaded by [`Self::load`].
+	#[clap(skip)]
+	pub old: DbWeights,
+
+	/// The DB weights of the new revision as loaded by [`Self::load`].
+	#[clap(skip)]
+	pub new: DbWeights,
+}
+
+/// The old or new side of a comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Revision {
+	Old,
+	New,
+}
+
+/// Weight of a single storage read and write in pico seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbWeights {
+	pub read: u128,
+	pub write: u128,
+}
+
+impl Default for DbWeights {
+	/// The RocksDB weights of Substrate.
+	fn default() -> Self {
+		Self { read: 25_000_000, write: 100_000_000 }
+	}
+}
+
+impl DbWeights {
+	/// Loads the weights from a storage weight file like `paritydb_weights.rs`.
+	pub fn from_file(path: &Path) -> Result<Self, Error> {
+		let parsed = parse::storage::parse_file(path)?;
+		let scope = SimpleScope::from_substrate();
+		let eval = |t: &SimpleTerm| t.eval(&scope).map_err(|e| e.in_file(path));
+
+		Ok(Self { read: eval(&parsed.weights.read)?, write: eval(&parsed.weights.write)? })
+	}
+}
+
+impl DbParams {
+	/// Loads the DB weight file of a revision, if any.
+	///
+	/// A relative path is resolved against `base`. Must be called while `base` is at the revision.
+	pub fn load(&mut self, revision: Revision, base: &Path) -> Result<(), Error> {
+		let file = match revision {
+			Revision::Old => self.old_db_weights.as_ref(),
+			Revision::New => self.new_db_weights.as_ref(),
+		};
+		let Some(file) = file.or(self.db_weights.as_ref()) else { return Ok(()) };
+		let weights = DbWeights::from_file(&base.join(file))?;
+		log::info!(
+			"Using DB weights of {} for the {:?} revision: {:?}",
+			file.display(),
+			revision,
+			weights
+		);
+
+		match revision {
+			Revision::Old => self.old = weights,
+			Revision::New => self.new = weights,
+		}
+		Ok(())
+	}
+
+	/// The DB weights of a revision with the `--read` and `--write` overrides applied.
+	pub fn weights(&self, revision: Revision) -> DbWeights {
+		let loaded = match revision {
+			Revision::Old => self.old,
+			Revision::New => self.new,
+		};
+		DbWeights {
+			read: self.read.unwrap_or(loaded.read),
+			write: self.write.unwrap_or(loaded.write),
+		}
+	}
+}
+
+/// Parameters for adding the `ExtrinsicBaseWeight` to each extrinsic.
+///
+/// This compares what a whole transaction costs instead of only its dispatch. Small weights then no
+/// longer show inflated relative changes.
+#[derive(Debug, Clone, PartialEq, Args, Default)]
+pub struct OverheadParams {
+	/// Overhead weight file with the `ExtrinsicBaseWeight` of both revisions, for example
+	/// `extrinsic_weights.rs`.
+	///
+	/// Relative to the repository when comparing commits; it is then read at each revision.
+	#[clap(long, value_name = "FILE")]
+	pub extrinsic_base: Option<PathBuf>,
+
+	/// Overhead weight file of the old revision. Overrides `--extrinsic-base`.
+	#[clap(long, value_name = "FILE")]
+	pub old_extrinsic_base: Option<PathBuf>,
+
+	/// Overhead weight file of the new revision. Overrides `--extrinsic-base`.
+	#[clap(long, value_name = "FILE")]
+	pub new_extrinsic_base: Option<PathBuf>,
+
+	/// The `ExtrinsicBaseWeight` of the old revision as loaded by [`Self::load`].
+	#[clap(skip)]
+	pub old: Option<traits::Weight>,
+
+	/// The `ExtrinsicBaseWeight` of the new revision as loaded by [`Self::load`].
+	#[clap(skip)]
+	pub new: Option<traits::Weight>,
+}
+
+impl OverheadParams {
+	/// Loads the overhead weight file of a revision, if any.
+	///
+	/// A relative path is resolved against `base`. Must be called while `base` is at the revision.
+	pub fn load(&mut self, revision: Revision, base: &Path) -> Result<(), Error> {
+		let file = match revision {
+			Revision::Old => self.old_extrinsic_base.as_ref(),
+			Revision::New => self.new_extrinsic_base.as_ref(),
+		};
+		let Some(file) = file.or(self.extrinsic_base.as_ref()) else { return Ok(()) };
+		let path = base.join(file);
+
+		let term = match parse::overhead::parse_file(&path)? {
+			parse::overhead::Weight::ExtrinsicBase(term) => term,
+			parse::overhead::Weight::BlockExecution(_) =>
+				return Err(Error::InvalidParam {
+					msg: format!(
+						"Expected an ExtrinsicBaseWeight but found a BlockExecutionWeight in {}",
+						path.display()
+					),
+				}),
+		};
+		let weight = parse::substitute_constants(term)
+			.eval(&scope::ChromaticScope::empty())
+			.map_err(|e| e.in_file(&path))?;
+		log::info!(
+			"Using ExtrinsicBaseWeight of {} for the {:?} revision: {:?}",
+			file.display(),
+			revision,
+			weight
+		);
+
+		match revision {
+			Revision::Old => self.old = Some(weight),
+			Revision::New => self.new = Some(weight),
+		}
+		Ok(())
+	}
+
+	/// The `ExtrinsicBaseWeight` of a revision, if loaded.
+	pub fn weight(&self, revision: Revision) -> Option<traits::Weight> {
+		match revision {
+			Revision::Old => self.old.clone(),
+			Revision::New => self.new.clone(),
+		}
+	}
+
+	/// Adds the `ExtrinsicBaseWeight` of a revision to the extrinsics of all pallets.
+	pub fn apply(
+		&self,
+		revision: Revision,
+		extrinsics: Vec<ChromaticExtrinsic>,
+	) -> Vec<ChromaticExtrinsic> {
+		let Some(weight) = self.weight(revision) else { return extrinsics };
+
+		extrinsics
+			.into_iter()
+			.map(|mut e| {
+				if e.kind == WeightKind::Pallet {
+					e.term =
+						term::Term::Add(e.term.into(), term::Term::Value(weight.clone()).into());
+				}
+				e
+			})
+			.collect()
+	}
+}
+
 impl CompareParams {
 	pub fn should_pull(&self) -> bool {
 		self.git_pull && !self.offline
 	}
+
+	/// Loads the DB and overhead weight files of a revision, see [`DbParams::load`].
+	pub fn load(&mut self, revision: Revision, base: &Path) -> Result<(), Error> {
+		self.db.load(revision, base)?;
+		self.overhead.load(revision, base)
+	}
+}
+
+impl BudgetParams {
+	/// The maximal weight that a single extrinsic may have in this dimension.
+	pub fn limit(&self, unit: Dimension) -> Option<u128> {
+		let max = match unit {
+			Dimension::Time => self.max_block_time,
+			Dimension::Proof => self.max_block_proof,
+		}?;
+		Some((max as f64 * self.max_block_ratio) as u128)
+	}
+}
+
+/// Parses a ratio in the range `(0, 1]`.
+pub fn parse_ratio(s: &str) -> Result<f64, String> {
+	let ratio = s.parse::<f64>().map_err(|e| format!("Invalid ratio {}: {}", s, e))?;
+	if ratio > 0.0 && ratio <= 1.0 {
+		Ok(ratio)
+	} else {
+		Err(format!("Ratio must be in (0, 1] but is {}", s))
+	}
+}
+
+impl Default for CompareParams {
+	fn default() -> Self {
+		Self {
+			method: CompareMethod::Base,
+			unit: Dimension::Time,
+			chromatic: None,
+			budget: Default::default(),
+			db: Default::default(),
+			overhead: Default::default(),
+			impls: Default::default(),
+			renames: Default::default(),
+			ignore_errors: false,
+			git_pull: false,
+			git_force: false,
+			offline: false,
+		}
+	}
+}
+
+impl Default for BudgetParams {
+	fn default() -> Self {
+		Self { max_block_time: None, max_block_proof: None, max_block_ratio: 0.75 }
+	}
 }
 
 pub fn compare_commits(
@@ -177,36 +746,104 @@ pub fn compare_commits(
 	filter: &FilterParams,
 	path_pattern: &str,
 	max_files: usize,
-) -> Result<TotalDiff, Box<dyn std::error::Error>> {
+) -> Result<TotalDiff, Error> {
+	let (olds, news, params) = parse_commits(repo, old, new, params, path_pattern, max_files)?;
+	compare_files(olds, news, &params, filter)
+}
+
+/// Compares the weight files of two runtimes at the same revision.
+///
+/// The `left` runtime is the old and the `right` one the new side. Both are directories relative to
+/// the repo in which the `path_pattern` is matched. Their pallets are matched by their path
+/// relative to the runtime directory and their extrinsics by name. Checks out `rev` first if given,
+/// otherwise the working tree is used.
+pub fn compare_runtime_dirs(
+	repo: &Path,
+	rev: Option<&str>,
+	left: &Path,
+	right: &Path,
+	params: &CompareParams,
+	filter: &FilterParams,
+	path_pattern: &str,
+) -> Result<TotalDiff, Error> {
 	if path_pattern.contains("..") {
-		return Err("Path pattern cannot contain '..'".into())
+		return Err(Error::InvalidParam { msg: "Path pattern cannot contain '..'".into() })
 	}
-	// Parse the old files.
-	if let Err(err) = git_checkout(repo, old, params.should_pull(), params.git_force) {
-		return Err(format!("{:?}", err).into())
+	for dir in [left, right] {
+		if dir.is_absolute() || dir.components().any(|c| c == std::path::Component::ParentDir) {
+			return Err(Error::InvalidParam {
+				msg: format!("Runtime directory {} must be relative to the repo", dir.display()),
+			})
+		}
+	}
+	let mut params = params.clone();
+	if let Some(rev) = rev {
+		git_checkout(repo, rev, params.should_pull(), params.git_force)?;
 	}
+	params.load(Revision::Old, repo)?;
+	params.load(Revision::New, repo)?;
+
+	let parse = |dir: &Path| {
+		let base = repo.join(dir);
+		let paths = list_files(&base, path_pattern, usize::MAX)?;
+		if paths.is_empty() {
+			return Err(Error::InvalidParam {
+				msg: format!("No files in {} match the path pattern", dir.display()),
+			})
+		}
+		// Relative to the runtime, so that the pallets of both runtimes match but files with the
+		// same name in different subdirectories do not.
+		if params.ignore_errors {
+			Ok(parse::try_parse_files_in_repo(&base, &paths, &params.impls))
+		} else {
+			parse::parse_files_in_repo(&base, &paths, &params.impls)
+		}
+	};
+	let olds = parse(left)?;
+	let news = parse(right)?;
+
+	compare_files(olds, news, &params, filter)
+}
+
+/// Parses the weight files of both commits.
+///
+/// Also returns the `params` with the DB and overhead weights of both revisions loaded.
+pub fn parse_commits(
+	repo: &Path,
+	old: &str,
+	new: &str,
+	params: &CompareParams,
+	path_pattern: &str,
+	max_files: usize,
+) -> Result<(Vec<ChromaticExtrinsic>, Vec<ChromaticExtrinsic>, CompareParams), Error> {
+	if path_pattern.contains("..") {
+		return Err(Error::InvalidParam { msg: "Path pattern cannot contain '..'".into() })
+	}
+	let mut params = params.clone();
+	// Parse the old files.
+	git_checkout(repo, old, params.should_pull(), params.git_force)?;
+	params.load(Revision::Old, repo)?;
 	let paths = list_files(repo, path_pattern, max_files)?;
 	// Ignore any parsing errors.
 	let olds = if params.ignore_errors {
-		try_parse_files_in_repo(repo, &paths)
+		try_parse_files_in_repo(repo, &paths, &params.impls)
 	} else {
 		// TODO use option for repo
-		parse_files_in_repo(repo, &paths)?
+		parse_files_in_repo(repo, &paths, &params.impls)?
 	};
 
 	// Parse the new files.
-	if let Err(err) = git_checkout(repo, new, params.should_pull(), params.git_force) {
-		return Err(format!("{:?}", err).into())
-	}
+	git_checkout(repo, new, params.should_pull(), params.git_force)?;
+	params.load(Revision::New, repo)?;
 	let paths = list_files(repo, path_pattern, max_files)?;
 	// Ignore any parsing errors.
 	let news = if params.ignore_errors {
-		try_parse_files_in_repo(repo, &paths)
+		try_parse_files_in_repo(repo, &paths, &params.impls)
 	} else {
-		parse_files_in_repo(repo, &paths)?
+		parse_files_in_repo(repo, &paths, &params.impls)?
 	};
 
-	compare_files(olds, news, params, filter)
+	Ok((olds, news, params))
 }
 
 pub fn git_checkout(
@@ -214,7 +851,7 @@ pub fn git_checkout(
 	refname: &str,
 	should_pull: bool,
 	force: bool,
-) -> Result<(), String> {
+) -> Result<(), Error> {
 	if force {
 		return git_reset(path, refname, should_pull)
 	}
@@ -231,19 +868,18 @@ pub fn git_checkout(
 		.arg(refname)
 		.current_dir(path)
 		.output()
-		.map_err(|e| format!("Failed to checkout branch: {:?}", e))?;
+		.map_err(|e| Error::Git { msg: format!("Failed to checkout branch: {:?}", e) })?;
 
 	if !output.status.success() {
-		return Err(format!(
-			"Failed to checkout branch: {}",
-			String::from_utf8_lossy(&output.stderr),
-		))
+		return Err(Error::Git {
+			msg: format!("Failed to checkout branch: {}", String::from_utf8_lossy(&output.stderr)),
+		})
 	}
 
 	Ok(())
 }
 
-pub fn git_pull(path: &Path, refname: &str) -> Result<(), String> {
+pub fn git_pull(path: &Path, refname: &str) -> Result<(), Error> {
 	log::info!("Fetching branch {}", refname);
 
 	let output = Command::new("git")
@@ -252,16 +888,18 @@ pub fn git_pull(path: &Path, refname: &str) -> Result<(), String> {
 		.arg(refname)
 		.current_dir(path)
 		.output()
-		.map_err(|e| format!("Failed to fetch branch: {:?}", &e))?;
+		.map_err(|e| Error::Git { msg: format!("Failed to fetch branch: {:?}", &e) })?;
 
 	if !output.status.success() {
-		return Err(format!("Failed to fetch branch: {}", String::from_utf8_lossy(&output.stderr),))
+		return Err(Error::Git {
+			msg: format!("Failed to fetch branch: {}", String::from_utf8_lossy(&output.stderr)),
+		})
 	}
 
 	Ok(())
 }
 
-pub fn git_reset(path: &Path, refname: &str, pull: bool) -> Result<(), String> {
+pub fn git_reset(path: &Path, refname: &str, pull: bool) -> Result<(), Error> {
 	if pull {
 		git_pull(path, refname)?;
 	} else {
@@ -293,35 +931,34 @@ pub fn git_reset(path: &Path, refname: &str, pull: bool) -> Result<(), String> {
 		.arg(refname)
 		.current_dir(path)
 		.output()
-		.map_err(|e| format!("Failed to reset branch: {:?}", e))?;
+		.map_err(|e| Error::Git { msg: format!("Failed to reset branch: {:?}", e) })?;
 
 	if !output.status.success() {
-		return Err(format!("Failed to reset branch: {}", String::from_utf8_lossy(&output.stderr)))
+		return Err(Error::Git {
+			msg: format!("Failed to reset branch: {}", String::from_utf8_lossy(&output.stderr)),
+		})
 	}
 	Ok(())
 }
 
-fn list_files(
-	base_path: &Path,
-	regex: &str,
-	max_files: usize,
-) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+fn list_files(base_path: &Path, regex: &str, max_files: usize) -> Result<Vec<PathBuf>, Error> {
 	let regex = regex.split(',');
 
 	let mut paths = Vec::new();
 	for regex in regex {
 		let regex = format!("{}/{}", base_path.display(), regex);
 		log::info!("Listing files matching: {:?}", &regex);
-		let files = glob::glob(&regex).map_err(|e| format!("Invalid path pattern: {:?}", e))?;
+		let files = glob::glob(&regex)
+			.map_err(|e| Error::InvalidParam { msg: format!("Invalid path pattern: {:?}", e) })?;
 		let files = files
 			.collect::<Result<Vec<_>, _>>()
-			.map_err(|e| format!("Path pattern error: {:?}", e))?;
+			.map_err(|e| Error::InvalidParam { msg: format!("Path pattern error: {:?}", e) })?;
 		let files: Vec<_> = files.iter().filter(|f| !f.ends_with("mod.rs")).cloned().collect();
 		paths.extend(files);
 		if paths.len() > max_files {
-			return Err(
-				format!("Found too many files. Found: {}, Max: {}", paths.len(), max_files).into()
-			)
+			return Err(Error::InvalidParam {
+				msg: format!("Found too many files. Found: {}, Max: {}", paths.len(), max_files),
+			})
 		}
 	}
 	paths.sort();
@@ -329,7 +966,9 @@ fn list_files(
 	Ok(paths)
 }
 
-#[derive(serde::Deserialize, clap::ValueEnum, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(
+	serde::Serialize, serde::Deserialize, clap::ValueEnum, PartialEq, Eq, Hash, Clone, Copy, Debug,
+)]
 #[serde(rename_all = "kebab-case")]
 pub enum CompareMethod {
 	/// The constant base weight of the extrinsic.
@@ -415,6 +1054,46 @@ pub enum Dimension {
 	Proof,
 }
 
+impl core::fmt::Display for Dimension {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Dimension::Time => write!(f, "time"),
+			Dimension::Proof => write!(f, "proof"),
+		}
+	}
+}
+
+/// Selects one of the two dimensions of a [`ChromaticDiff`].
+#[derive(
+	serde::Serialize, serde::Deserialize, clap::ValueEnum, PartialEq, Eq, Hash, Clone, Copy, Debug,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum DimensionSelector {
+	/// Use the [`Dimension::Time`] change.
+	Time,
+	/// Use the [`Dimension::Proof`] change.
+	Proof,
+	/// Use whichever change of the two is worse.
+	Worst,
+}
+
+impl DimensionSelector {
+	pub fn select(&self, diff: &ChromaticDiff) -> TermDiff {
+		match self {
+			Self::Time => diff.time.clone(),
+			Self::Proof => diff.proof.clone(),
+			Self::Worst => match (&diff.time, &diff.proof) {
+				(TermDiff::Failed(_), _) => diff.time.clone(),
+				(_, TermDiff::Failed(_)) => diff.proof.clone(),
+				(TermDiff::OverBudget { .. }, _) => diff.time.clone(),
+				(_, TermDiff::OverBudget { .. }) => diff.proof.clone(),
+				(time, proof) if time.cmp(proof) == Ordering::Greater => time.clone(),
+				(_, proof) => proof.clone(),
+			},
+		}
+	}
+}
+
 impl std::str::FromStr for CompareMethod {
 	type Err = String;
 
@@ -477,6 +1156,7 @@ impl std::str::FromStr for RelativeChange {
 			"changed" => Ok(Self::Changed),
 			"added" => Ok(Self::Added),
 			"removed" => Ok(Self::Removed),
+			"renamed" => Ok(Self::Renamed),
 			_ => Err(format!("Unknown change: {}", s)),
 		}
 	}
@@ -484,134 +1164,254 @@ impl std::str::FromStr for RelativeChange {
 
 impl RelativeChange {
 	pub fn variants() -> Vec<&'static str> {
-		vec!["unchanged", "changed", "added", "removed"]
+		vec!["unchanged", "changed", "added", "removed", "renamed"]
 	}
 }
 
 pub fn compare_extrinsics(
-	mut old: Option<SimpleExtrinsic>,
-	mut new: Option<SimpleExtrinsic>,
+	old: Option<SimpleExtrinsic>,
+	new: Option<SimpleExtrinsic>,
 	params: &CompareParams,
-) -> Result<TermChange, String> {
-	let mut scope = scope::SimpleScope::empty();
-	if params.unit == Dimension::Time {
-		scope = scope
-			.with_storage_weights(SimpleTerm::Scalar(25_000_000), SimpleTerm::Scalar(100_000_000));
+) -> Result<TermChange, Error> {
+	let scope = storage_scope(params.unit, params.db.weights(Revision::New));
+	let old = old.map(|o| strip_storage(o, params.unit));
+	let new = new.map(|n| strip_storage(n, params.unit));
+	let (new, old) = (new.as_ref(), old.as_ref());
+	let (old_t, new_t) = (old.map(|o| &o.term), new.map(|n| &n.term));
+	let bounds = component_bounds(old, new, params.method, &scope)?;
+
+	// The scope has the DB weights of the new revision; the old term gets its own substituted.
+	let old_scope = storage_scope(params.unit, params.db.weights(Revision::Old));
+	let old_db = old_t.filter(|_| old_scope != scope).map(|t| {
+		let mut t = t.clone();
+		for var in [scope::STORAGE_READ_VAR, scope::STORAGE_WRITE_VAR] {
+			t.substitute(var, &old_scope.get(var).expect("Storage scope has it; qed"));
+		}
+		t
+	});
+	let old_e = old_db.as_ref().or(old_t);
+
+	let objective =
+		if old.is_some() && new.is_some() { Objective::Relative } else { Objective::Value };
+	let worst = worst::find_worst(old_e, new_t, &bounds, &scope, objective)?;
+	let mut change = compare_terms(old_e, new_t, params.method, &worst)?;
+
+	// The relative and absolute worst case can be at different components.
+	if objective == Objective::Relative && bounds.values().any(|(lo, hi)| lo != hi) {
+		let scope = worst::find_worst(old_e, new_t, &bounds, &scope, Objective::Absolute)?;
+		let absolute = compare_terms(old_e, new_t, params.method, &scope)?;
+		change.absolute = AbsoluteChange::new(&change, absolute);
+	}
+	change.old = old_t.cloned();
+	Ok(change)
+}
+
+/// Compares the storage accesses of two versions of an extrinsic.
+///
+/// Returns only the storage items that were added, removed or whose read, write or `max_size`
+/// changed. Repeated accesses of the same item are summed up.
+pub fn compare_storage<T>(
+	old: Option<&GenericExtrinsic<T>>,
+	new: Option<&GenericExtrinsic<T>>,
+) -> Vec<StorageChange> {
+	fn by_key(ext: Option<&[StorageAccess]>) -> BTreeMap<(String, String), StorageAccess> {
+		let mut res = BTreeMap::<_, StorageAccess>::new();
+		for access in ext.unwrap_or_default() {
+			res.entry((access.pallet.clone(), access.item.clone()))
+				.and_modify(|a| {
+					a.reads = a.reads.saturating_add(access.reads);
+					a.writes = a.writes.saturating_add(access.writes);
+				})
+				.or_insert_with(|| access.clone());
+		}
+		res
+	}
+	let mut old = by_key(old.map(|o| o.storage.as_slice()));
+	let mut new = by_key(new.map(|n| n.storage.as_slice()));
+	let keys = old.keys().chain(new.keys()).cloned().collect::<BTreeSet<_>>();
+
+	keys.into_iter()
+		.map(|key| {
+			let (old, new) = (old.remove(&key), new.remove(&key));
+			StorageChange { pallet: key.0, item: key.1, old, new }
+		})
+		.filter(|c| c.change() != RelativeChange::Unchanged)
+		.collect()
+}
+
+/// Compares the coefficients of the [`SimpleTerm::normalize`]d terms monomial by monomial.
+///
+/// A monomial that only exists in one of the terms is `Added` or `Removed`. Returns nothing if
+/// a term cannot be normalized.
+pub fn compare_coefficients(
+	old: Option<&SimpleTerm>,
+	new: Option<&SimpleTerm>,
+) -> Vec<CoefficientChange> {
+	let coefficients = |t: Option<&SimpleTerm>| t.map(|t| t.coefficients());
+	let (old, new) = match (coefficients(old), coefficients(new)) {
+		(Some(None), _) | (_, Some(None)) => return Vec::new(),
+		(old, new) => (old.flatten().unwrap_or_default(), new.flatten().unwrap_or_default()),
+	};
+	let monomials = old.keys().chain(new.keys()).cloned().collect::<BTreeSet<_>>();
+
+	monomials
+		.into_iter()
+		.filter_map(|monomial| {
+			let change =
+				ValueChange::new(old.get(&monomial).copied(), new.get(&monomial).copied())?;
+			Some(CoefficientChange { monomial, change })
+		})
+		.collect()
+}
+
+/// Checks that the worst case weight of an extrinsic stays within the block budget.
+///
+/// The worst case is searched within the component ranges, see [`worst::find_worst`]. Returns a
+/// message if the budget is exceeded.
+pub fn check_block_budget(
+	ext: &SimpleExtrinsic,
+	params: &CompareParams,
+) -> Result<Option<String>, Error> {
+	let Some(budget) = params.budget.limit(params.unit) else { return Ok(None) };
+	let method = if params.method.max().exact {
+		CompareMethod::ExactWorst
 	} else {
-		scope = scope.with_storage_weights(SimpleTerm::Scalar(0), SimpleTerm::Scalar(0));
-		// OMG this code is stupid... but since READ and WRITE done incur proof size cost, we ignore
-		// them.
-		old = old.map(|mut o| {
-			o.term.substitute("READ", &scalar!(0));
-			o
-		});
-		old = old.map(|mut o| {
-			o.term.substitute("WRITE", &scalar!(0));
-			o
-		});
-		new = new.map(|mut o| {
-			o.term.substitute("READ", &scalar!(0));
-			o
-		});
-		new = new.map(|mut o| {
-			o.term.substitute("WRITE", &scalar!(0));
-			o
-		});
+		CompareMethod::GuessWorst
+	};
+	let scope = storage_scope(params.unit, params.db.weights(Revision::New));
+	let ext = strip_storage(ext.clone(), params.unit);
+
+	let bounds = component_bounds(Some(&ext), None, method, &scope)?;
+	let worst_scope = worst::find_worst(None, Some(&ext.term), &bounds, &scope, Objective::Value)?;
+	let worst = ext.term.eval(&worst_scope)?;
+	if worst <= budget {
+		return Ok(None)
 	}
-	let (new, old) = (new.as_ref(), old.as_ref());
-	let scopes = extend_scoped_components(old, new, params.method, &scope)?;
-	let name = old.map(|o| o.name.clone()).or_else(|| new.map(|n| n.name.clone())).unwrap();
-	let pallet = old.map(|o| o.pallet.clone()).or_else(|| new.map(|n| n.pallet.clone())).unwrap();
-
-	let mut results = Vec::<TermChange>::new();
-
-	for scope in scopes.iter() {
-		if !old.map_or(true, |e| e.term.free_vars(scope).is_empty()) {
-			unreachable!(
-				"Free variable where there should be none: {}::{} {:?}",
-				name,
-				&pallet,
-				old.unwrap().term.free_vars(scope)
-			);
-		}
-		assert!(new.map_or(true, |e| e.term.free_vars(scope).is_empty()));
-		// NOTE: The maximum could be calculated right here, but for now I want the debug assert.
-		results.push(compare_terms(
-			old.map(|o| &o.term),
-			new.map(|n| &n.term),
-			params.method,
-			scope,
-		)?);
-	}
-	log::trace!(target: "compare", "{}::{} Evaluated {} scopes", pallet, name, scopes.len());
-
-	// Sanity check: They are either
-	// All Increase/Decrease/Unchanged OR
-	// All Added/Removed
-	let all_increase_or_decrease = results
-		.iter()
-		.all(|r| matches!(r.change, RelativeChange::Changed | RelativeChange::Unchanged));
-	let all_added_or_removed = results
-		.iter()
-		.all(|r| matches!(r.change, RelativeChange::Added | RelativeChange::Removed));
 
-	if all_added_or_removed {
-		// Just pick the first one
-		Ok(results.into_iter().next().unwrap())
-	} else if all_increase_or_decrease {
-		Ok(results.into_iter().max_by(|a, b| a.cmp(b)).unwrap())
+	let mut msg = format!(
+		"Worst case {} exceeds {}% of the max block {} ({})",
+		params.unit.fmt_value(worst),
+		params.budget.max_block_ratio * 100.0,
+		params.unit,
+		params.unit.fmt_value(budget),
+	);
+	let components = ext
+		.term
+		.free_vars(&scope)
+		.into_iter()
+		.map(|c| format!("{}={}", c, worst_scope.get(&c).expect("Component is in scope; qed")))
+		.collect::<Vec<_>>();
+	if !components.is_empty() {
+		msg.push_str(&format!(" at {}", components.join(", ")));
+	}
+	Ok(Some(msg))
+}
+
+/// Checks whether the weight of an extrinsic saturates at `u64::MAX` within its component ranges.
+///
+/// Substrate computes weights with saturating `u64` arithmetic, so such a weight is capped by the
+/// runtime. Returns a message with the components at which it saturates. The extrinsic is
+/// evaluated with the DB weights of its `revision`.
+pub fn check_saturation(
+	ext: &SimpleExtrinsic,
+	revision: Revision,
+	params: &CompareParams,
+) -> Result<Option<String>, Error> {
+	let method = if params.method.max().exact {
+		CompareMethod::ExactWorst
 	} else {
-		unreachable!(
-			"Inconclusive: all_increase_or_decrease: {}, all_added_or_removed: {}",
-			all_increase_or_decrease, all_added_or_removed
-		);
+		CompareMethod::GuessWorst
+	};
+	let scope = storage_scope(params.unit, params.db.weights(revision));
+	let ext = strip_storage(ext.clone(), params.unit);
+
+	// Weight terms only grow with their components, so do their intermediate results.
+	let bounds = component_bounds(Some(&ext), None, method, &scope)?;
+	let worst_scope = worst::find_worst(None, Some(&ext.term), &bounds, &scope, Objective::Value)?;
+	if !ext.term.eval_saturating(&worst_scope)?.1 {
+		return Ok(None)
 	}
+
+	let mut msg = "weight saturates at u64::MAX".to_string();
+	let components = ext
+		.term
+		.free_vars(&scope)
+		.into_iter()
+		.map(|c| format!("{}={}", c, worst_scope.get(&c).expect("Component is in scope; qed")))
+		.collect::<Vec<_>>();
+	if !components.is_empty() {
+		msg.push_str(&format!(" with {}", components.join(", ")));
+	}
+	Ok(Some(msg))
 }
 
+/// The weight of an extrinsic in both dimensions with the components chosen by `method`.
+///
+/// [`CompareMethod::Base`] sets the components to their minimum and the asymptotic methods to
+/// their maximum. The worst case methods search the component values with the largest weight.
+pub fn evaluate_extrinsic(
+	ext: &ChromaticExtrinsic,
+	method: CompareMethod,
+	db: DbWeights,
+) -> Result<traits::Weight, Error> {
+	let eval = |unit| {
+		let term = ext.term.simplify(unit)?;
+		let scope = storage_scope(unit, db);
+		let ext = strip_storage(ext.clone().map_term(|_| term.clone()), unit);
+
+		let bounds = component_bounds(Some(&ext), None, method, &scope)?;
+		let worst = worst::find_worst(None, Some(&ext.term), &bounds, &scope, Objective::Value)?;
+		ext.term.eval(&worst)
+	};
+	Ok(traits::Weight { time: eval(Dimension::Time)?, proof: eval(Dimension::Proof)? })
+}
+
+/// The scope that contains the storage weights for the given dimension.
+fn storage_scope(unit: Dimension, db: DbWeights) -> SimpleScope {
+	match unit {
+		Dimension::Time => SimpleScope::empty()
+			.with_storage_weights(SimpleTerm::Scalar(db.read), SimpleTerm::Scalar(db.write)),
+		Dimension::Proof =>
+			SimpleScope::empty().with_storage_weights(SimpleTerm::Scalar(0), SimpleTerm::Scalar(0)),
+	}
+}
+
+/// Removes the storage weights from the term if they do not apply to this dimension.
+fn strip_storage(mut ext: SimpleExtrinsic, unit: Dimension) -> SimpleExtrinsic {
+	// Since READ and WRITE do not incur proof size cost, we ignore them.
+	if unit == Dimension::Proof {
+		ext.term.substitute("READ", &scalar!(0));
+		ext.term.substitute("WRITE", &scalar!(0));
+	}
+	ext
+}
+
+/// The lowest and highest value of each free component of the extrinsics.
+///
+/// They are the min and max of the component ranges or guessed, depending on the method.
 // TODO handle case that both have (different) ranges.
-pub(crate) fn extend_scoped_components(
+pub(crate) fn component_bounds(
 	a: Option<&SimpleExtrinsic>,
 	b: Option<&SimpleExtrinsic>,
 	method: CompareMethod,
 	scope: &SimpleScope,
-) -> Result<Vec<SimpleScope>, String> {
+) -> Result<ComponentBounds, Error> {
 	let free_a = a.map(|e| e.term.free_vars(scope)).unwrap_or_default();
 	let free_b = b.map(|e| e.term.free_vars(scope)).unwrap_or_default();
-	let frees = free_a.union(&free_b).cloned().collect::<HashSet<_>>();
 
 	let ra = a.map(|ext| ext.clone().comp_ranges.unwrap_or_default());
 	let rb = b.map(|ext| ext.clone().comp_ranges.unwrap_or_default());
 
 	let (pallet, extrinsic) = a.or(b).map(|e| (e.pallet.clone(), e.name.clone())).unwrap();
 
-	if frees.len() > 16 {
-		return Err(format!(
-			"Too many components to compare: {}::{} has {} components - limit is 16",
-			pallet,
-			extrinsic,
-			frees.len()
-		))
-	}
-	// Combine the maximum and minimum of each component with combinatorics.
-	let (mut lowest, mut highest) = (Vec::new(), Vec::new());
-	for free in frees.iter() {
-		lowest.push(instance_component(free, &ra, &rb, method.min(), &pallet, &extrinsic)?);
-		highest.push(instance_component(free, &ra, &rb, method.max(), &pallet, &extrinsic)?);
-	}
-
-	// cartesian product of lowest and highest
-	let mut scopes = BTreeSet::new();
-	for i in 0..(1 << frees.len()) {
-		let mut scope = scope.clone();
-		for (c, component) in frees.iter().enumerate() {
-			let value = if i & (1 << c) == 0 { lowest[c] } else { highest[c] };
-			scope.put_var(component, SimpleTerm::Scalar(value as u128));
-		}
-		if !scope.is_empty() {
-			scopes.insert(scope);
-		}
-	}
-	Ok(scopes.into_iter().collect())
+	free_a
+		.union(&free_b)
+		.map(|free| {
+			let lowest = instance_component(free, &ra, &rb, method.min(), &pallet, &extrinsic)?;
+			let highest = instance_component(free, &ra, &rb, method.max(), &pallet, &extrinsic)?;
+			Ok((free.clone(), (lowest, highest)))
+		})
+		.collect()
 }
 
 fn instance_component(
@@ -621,7 +1421,7 @@ fn instance_component(
 	strategy: ComponentInstanceStrategy,
 	pallet: &str,
 	extrinsic: &str,
-) -> Result<u32, String> {
+) -> Result<u32, Error> {
 	use MinOrMax::*;
 
 	match (ra.as_ref().and_then(|r| r.get(component)), rb.as_ref().and_then(|r| r.get(component))) {
@@ -637,10 +1437,10 @@ fn instance_component(
 		}),
 		// Both extrinsics have different ranges? Bad, use the min/max.
 		(Some(ra), Some(rb)) => match (strategy.exact, strategy.min_or_max) {
-			(true, _) => Err(format!(
-				"Component {} of call {}::{} has different ranges in the old and new version - use Guess instead!",
-				component, pallet, extrinsic,
-			)),
+			(true, _) => Err(Error::DifferentRanges {
+				component: component.into(),
+				extrinsic: format!("{}::{}", pallet, extrinsic),
+			}),
 			(false, Min) => Ok(ra.min.min(rb.min)),
 			(false, Max) => Ok(ra.max.max(rb.max)),
 		},
@@ -648,10 +1448,10 @@ fn instance_component(
 		(None, None) => match (strategy.exact, strategy.min_or_max) {
 			(false, Min) => Ok(0),
 			(false, Max) => Ok(100),
-			(true, _) => Err(format!(
-				"No range for component {} of call {}::{} - use Guess instead!",
-				component, pallet, extrinsic,
-			)),
+			(true, _) => Err(Error::MissingRange {
+				component: component.into(),
+				extrinsic: format!("{}::{}", pallet, extrinsic),
+			}),
 		},
 	}
 }
@@ -661,7 +1461,7 @@ pub fn compare_terms(
 	new: Option<&SimpleTerm>,
 	method: CompareMethod,
 	scope: &SimpleScope,
-) -> Result<TermChange, String> {
+) -> Result<TermChange, Error> {
 	let old_v = old.map(|t| t.eval(scope)).transpose()?;
 	let new_v = new.map(|t| t.eval(scope)).transpose()?;
 	let change =
@@ -678,6 +1478,7 @@ pub fn compare_terms(
 		percent: p,
 		method,
 		scope: scope.clone(),
+		absolute: None,
 	})
 }
 
@@ -686,23 +1487,84 @@ pub fn compare_files(
 	news: Vec<ChromaticExtrinsic>,
 	params: &CompareParams,
 	filter: &FilterParams,
-) -> Result<TotalDiff, Box<dyn std::error::Error>> {
-	let ext_regex = filter.extrinsic.as_ref().map(|s| Regex::new(s)).transpose()?;
-	let pallet_regex = filter.pallet.as_ref().map(|s| Regex::new(s)).transpose()?;
+) -> Result<TotalDiff, Error> {
+	let olds = params.overhead.apply(Revision::Old, olds);
+	let news = params.overhead.apply(Revision::New, news);
+	// Detected on the chromatic terms so that both dimensions pair the same extrinsics.
+	let renames = rename::detect_renames(&olds, &news, &params.renames);
+	let Some(selector) = params.chromatic else {
+		return compare_files_in(&olds, &news, &renames, params.unit, params, filter)
+	};
+
+	let times = compare_files_in(&olds, &news, &renames, Dimension::Time, params, filter)?;
+	let proofs = compare_files_in(&olds, &news, &renames, Dimension::Proof, params, filter)?;
+	// Both are sorted by pallet and extrinsic name.
+	let diff = times
+		.into_iter()
+		.zip(proofs)
+		.map(|(time, proof)| {
+			debug_assert!(time.name == proof.name && time.file == proof.file);
+			let chromatic = ChromaticDiff { time: time.change, proof: proof.change };
+
+			let change = selector.select(&chromatic);
+			let coefficients = match change.term() {
+				Some(t) => compare_coefficients(t.old.as_ref(), t.new.as_ref()),
+				None => Vec::new(),
+			};
+
+			ExtrinsicDiff {
+				name: time.name,
+				file: time.file,
+				change,
+				chromatic: Some(chromatic),
+				storage: time.storage,
+				summary: time.summary,
+				coefficients,
+				renamed_from: time.renamed_from,
+				metadata: time.metadata,
+			}
+		})
+		.collect();
+
+	Ok(diff)
+}
+
+/// Compares the extrinsics in a single dimension.
+///
+/// The `renames` are compared under their new name and their old name is skipped.
+fn compare_files_in(
+	olds: &[ChromaticExtrinsic],
+	news: &[ChromaticExtrinsic],
+	renames: &[Rename],
+	unit: Dimension,
+	params: &CompareParams,
+	filter: &FilterParams,
+) -> Result<TotalDiff, Error> {
+	let regex = |s: &String| {
+		Regex::new(s).map_err(|e| Error::InvalidParam { msg: format!("Invalid regex: {}", e) })
+	};
+	let ext_regex = filter.extrinsic.as_ref().map(regex).transpose()?;
+	let pallet_regex = filter.pallet.as_ref().map(regex).transpose()?;
+	let params = CompareParams { unit, ..params.clone() };
 	// Split them into their correct dimension.
 	let olds = olds
-		.into_iter()
-		.map(|e| e.map_term(|t| t.simplify(params.unit).expect("Must simplify term")))
+		.iter()
+		.cloned()
+		.map(|e| e.map_term(|t| t.simplify(unit).expect("Must simplify term")))
 		.collect::<Vec<_>>();
 	let news = news
-		.into_iter()
-		.map(|e| e.map_term(|t| t.simplify(params.unit).expect("Must simplify term")))
+		.iter()
+		.cloned()
+		.map(|e| e.map_term(|t| t.simplify(unit).expect("Must simplify term")))
 		.collect::<Vec<_>>();
 
 	let mut diff = TotalDiff::new();
 	let old_names = olds.iter().cloned().map(|e| (e.pallet, e.name));
 	let new_names = news.iter().cloned().map(|e| (e.pallet, e.name));
-	let names = old_names.chain(new_names).collect::<std::collections::BTreeSet<_>>();
+	let names = old_names
+		.chain(new_names)
+		.filter(|(p, e)| !renames.iter().any(|r| &r.old_file == p && &r.old_name == e))
+		.collect::<std::collections::BTreeSet<_>>();
 	log::trace!("Comparing {} terms", olds.len());
 
 	for (pallet, extrinsic) in names {
@@ -714,37 +1576,97 @@ pub fn compare_files(
 			continue
 		}
 
+		let rename = renames.iter().find(|r| r.new_file == pallet && r.new_name == extrinsic);
+		let (old_pallet, old_name) =
+			rename.map_or((&pallet, &extrinsic), |r| (&r.old_file, &r.old_name));
 		let new = news.iter().find(|&n| n.name == extrinsic && n.pallet == pallet);
-		let old = olds.iter().find(|&n| n.name == extrinsic && n.pallet == pallet);
+		let old = olds.iter().find(|&n| &n.name == old_name && &n.pallet == old_pallet);
 		log::trace!("Comparing {}::{}", pallet, extrinsic);
 
-		let change = match compare_extrinsics(old.cloned(), new.cloned(), params) {
+		let change =
+			compare_extrinsics(old.cloned(), new.cloned(), &params).and_then(|mut change| {
+				if rename.is_some() {
+					change.change = RelativeChange::Renamed;
+				}
+				let over = new.map(|n| check_block_budget(n, &params)).transpose()?.flatten();
+				Ok((change, over))
+			});
+		let change = match change {
 			Err(err) => {
-				log::warn!("Parsing failed {}: {:?}", &pallet, err);
+				log::warn!("Parsing failed {}: {}", &pallet, err);
 				TermDiff::Failed(err)
 			},
-			Ok(change) =>
-				if let Some(ext) = new.or(old) {
-					if let Err(err) = sanity_check_term(&ext.term)
-						.map_err(|e| format!("{}: {}::{}", e, ext.pallet, ext.name))
-					{
-						TermDiff::Warning(change, err)
-					} else {
-						TermDiff::Changed(change)
+			Ok((change, over)) => {
+				let ext = new.or(old).expect(
+					"We already checked that the extrinsic exists in either old or new; qed",
+				);
+				let mut warnings = sanity_check_term(&ext.term)
+					.map_err(|e| format!("{}: {}::{}", e, ext.pallet, ext.name))
+					.err()
+					.into_iter()
+					.collect::<Vec<_>>();
+				for (revision, ext) in [(Revision::Old, old), (Revision::New, new)] {
+					// Errors are already reported by the comparison itself.
+					if let Some(Ok(Some(w))) = ext.map(|e| check_saturation(e, revision, &params)) {
+						warnings.push(format!("{:?} {}: {}::{}", revision, w, pallet, extrinsic));
 					}
-				} else {
-					unreachable!(
-						"We already checked that the extrinsic exists in either old or new"
-					)
-				},
+				}
+				match over {
+					Some(over) => TermDiff::OverBudget {
+						change,
+						message: format!("{}: {}::{}", over, pallet, extrinsic),
+						warnings,
+					},
+					None if warnings.is_empty() => TermDiff::Changed(change),
+					None => TermDiff::Warning { change, warnings },
+				}
+			},
 		};
 
-		diff.push(ExtrinsicDiff { name: extrinsic.clone(), file: pallet.clone(), change });
+		let coefficients = match change.term() {
+			Some(t) => compare_coefficients(t.old.as_ref(), t.new.as_ref()),
+			None => Vec::new(),
+		};
+		diff.push(ExtrinsicDiff {
+			name: extrinsic.clone(),
+			file: pallet.clone(),
+			change,
+			chromatic: None,
+			storage: compare_storage(old, new),
+			summary: SummaryDiff::new(old, new),
+			coefficients,
+			renamed_from: rename.cloned(),
+			metadata: compare_metadata(old, new),
+		});
 	}
 
 	Ok(diff)
 }
 
+/// Checks that the old and new weights were generated under the same conditions.
+///
+/// See [`parse::metadata::BenchmarkMetadata::compare`]. The metadata belongs to the file, so the
+/// result is the same for all extrinsics of a file.
+pub fn compare_metadata<T>(
+	old: Option<&GenericExtrinsic<T>>,
+	new: Option<&GenericExtrinsic<T>>,
+) -> Vec<String> {
+	let (Some(old), Some(new)) = (old, new) else { return Vec::new() };
+
+	match (&old.metadata, &new.metadata) {
+		(Some(o), Some(n)) => o.compare(n),
+		_ => Vec::new(),
+	}
+}
+
+/// The [`ExtrinsicDiff::metadata`] of each file that has any.
+pub fn metadata_mismatches(diff: &TotalDiff) -> BTreeMap<&String, &Vec<String>> {
+	diff.iter()
+		.filter(|d| !d.metadata.is_empty())
+		.map(|d| (&d.file, &d.metadata))
+		.collect()
+}
+
 /// Checks some obvious stuff:
 /// - Does not have more than 1000 reads or writes
 pub fn sanity_check_term(term: &SimpleTerm) -> Result<(), String> {
@@ -768,14 +1690,19 @@ pub fn sort_changes(diff: &mut TotalDiff) {
 }
 
 impl TermDiff {
+	pub fn term(&self) -> Option<&TermChange> {
+		match self {
+			TermDiff::Changed(change) => Some(change),
+			TermDiff::Warning { change, .. } | TermDiff::OverBudget { change, .. } => Some(change),
+			_ => None,
+		}
+	}
+
 	fn cmp(&self, other: &Self) -> Ordering {
-		match (&self, &other) {
-			(TermDiff::Failed(_), _) => Ordering::Less,
-			(_, TermDiff::Failed(_)) => Ordering::Greater,
-			(TermDiff::Warning(a, _), TermDiff::Changed(b)) => a.cmp(b),
-			(TermDiff::Changed(a), TermDiff::Warning(b, _)) => a.cmp(b),
-			(TermDiff::Warning(a, _), TermDiff::Warning(b, _)) => a.cmp(b),
-			(TermDiff::Changed(a), TermDiff::Changed(b)) => a.cmp(b),
+		match (self.term(), other.term()) {
+			(None, _) => Ordering::Less,
+			(_, None) => Ordering::Greater,
+			(Some(a), Some(b)) => a.cmp(b),
 		}
 	}
 }
@@ -802,8 +1729,11 @@ pub fn filter_changes(diff: TotalDiff, params: &FilterParams) -> TotalDiff {
 	// Note: the pallet and extrinsic are already filtered in compare_files.
 	diff.iter()
 		.filter(|extrinsic| match extrinsic.change {
+			_ if !extrinsic.over_budget().is_empty() => true,
 			TermDiff::Failed(_) => true,
-			TermDiff::Warning(ref change, ..) | TermDiff::Changed(ref change) => {
+			TermDiff::Warning { ref change, .. } |
+			TermDiff::OverBudget { ref change, .. } |
+			TermDiff::Changed(ref change) => {
 				if !params.included(&change.change) {
 					return false
 				}
@@ -831,6 +1761,79 @@ impl RelativeChange {
 	}
 }
 
+/// A condition on a [`TotalDiff`] that fails a CI run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailOn {
+	/// Any extrinsic could not be compared.
+	Failed,
+	/// Any extrinsic failed the sanity checks of [`sanity_check_term`].
+	Warning,
+	/// Any extrinsic increased by more than this percentage, also when it was renamed.
+	Changed(Percent),
+	/// Any extrinsic was added.
+	Added,
+	/// Any extrinsic was removed.
+	Removed,
+}
+
+impl FailOn {
+	/// Returns all extrinsics that violate this policy.
+	///
+	/// Chromatic extrinsics violate it if any of their dimensions does.
+	pub fn offenders<'a>(&self, diff: &'a TotalDiff) -> Vec<&'a ExtrinsicDiff> {
+		diff.iter()
+			.filter(|extrinsic| {
+				let mut terms = extrinsic.diffs().into_iter().filter_map(TermDiff::term);
+				match self {
+					Self::Failed => extrinsic.failed(),
+					Self::Warning => !extrinsic.warnings().is_empty(),
+					Self::Changed(threshold) => terms.any(|t| {
+						matches!(t.change, RelativeChange::Changed | RelativeChange::Renamed) &&
+							t.percent > *threshold
+					}),
+					Self::Added => terms.any(|t| t.change == RelativeChange::Added),
+					Self::Removed => terms.any(|t| t.change == RelativeChange::Removed),
+				}
+			})
+			.collect()
+	}
+}
+
+impl std::str::FromStr for FailOn {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		match s.split_once(':') {
+			None => match s {
+				"failed" => Ok(Self::Failed),
+				"warning" => Ok(Self::Warning),
+				"changed" => Ok(Self::Changed(0.0)),
+				"added" => Ok(Self::Added),
+				"removed" => Ok(Self::Removed),
+				_ => Err(format!("Unknown policy: {}", s)),
+			},
+			Some(("changed", percent)) => percent
+				.trim_end_matches('%')
+				.parse()
+				.map(Self::Changed)
+				.map_err(|e| format!("Invalid percent in policy {}: {}", s, e)),
+			Some(_) => Err(format!("Unknown policy: {}", s)),
+		}
+	}
+}
+
+impl core::fmt::Display for FailOn {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Failed => write!(f, "failed"),
+			Self::Warning => write!(f, "warning"),
+			Self::Changed(percent) => write!(f, "changed:{}", percent),
+			Self::Added => write!(f, "added"),
+			Self::Removed => write!(f, "removed"),
+		}
+	}
+}
+
 pub fn percent(old: u128, new: u128) -> Percent {
 	100.0 * (new as f64 / old as f64) - 100.0
 }
@@ -0,0 +1,493 @@
+//! Checks weight files for common mistakes.
+//!
+//! The parser attaches a [`Lint`] to each extrinsic whose components do not agree with its weight
+//! term. [`lint_files`] combines them with checks of the term itself and across files into
+//! [`Finding`]s of a [`Rule`].
+//!
+//! The components are the parameters of the weight function, like `s` in
+//! `fn withdraw_unbonded_update(s: u32, ) -> Weight`. The benchmarking CLI prefixes the
+//! parameters that do not influence the weight with an underscore, so these are not expected to
+//! appear in the term.
+
+use fancy_regex::Regex;
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap},
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	parse::{
+		pallet::{ChromaticExtrinsic, ComponentName, ComponentRanges, ImplParams, WeightKind},
+		read_file, report_file, PathStripping,
+	},
+	sanity_check_term,
+	scope::{Scope, SimpleScope, STORAGE_READ_VAR, STORAGE_WRITE_VAR},
+	term::ChromaticTerm,
+	Dimension, Error, ExtrinsicName,
+};
+
+/// A problem with the components of an extrinsic that the parser found.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(tag = "lint", rename_all = "kebab-case")]
+pub enum Lint {
+	/// A parameter of the weight function that does not appear in its term.
+	UnusedComponent { component: ComponentName },
+	/// A variable of the term that is neither a parameter nor a known constant.
+	UndeclaredComponent { component: ComponentName },
+	/// A parameter without a `The range of component` doc comment.
+	MissingRange { component: ComponentName },
+	/// The parameters of the weight function differ from those in the trait declaration.
+	SignatureMismatch { declared: Vec<ComponentName>, found: Vec<ComponentName> },
+	/// The weight function returns `Weight::zero()`.
+	ZeroPlaceholder,
+	/// The weight has no proof size since it uses `from_ref_time` or the V1 syntax.
+	MissingProof,
+}
+
+impl Lint {
+	/// Kebab-case name of the lint, for example `unused-component`.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::UnusedComponent { .. } => "unused-component",
+			Self::UndeclaredComponent { .. } => "undeclared-component",
+			Self::MissingRange { .. } => "missing-range",
+			Self::SignatureMismatch { .. } => "signature-mismatch",
+			Self::ZeroPlaceholder => "zero-placeholder",
+			Self::MissingProof => "missing-proof",
+		}
+	}
+}
+
+impl std::fmt::Display for Lint {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::UnusedComponent { component } =>
+				write!(f, "Component '{}' is not used in the weight term", component),
+			Self::UndeclaredComponent { component } =>
+				write!(f, "Variable '{}' is not a parameter of the weight function", component),
+			Self::MissingRange { component } =>
+				write!(f, "Component '{}' has no range annotation", component),
+			Self::SignatureMismatch { declared, found } => write!(
+				f,
+				"Parameters ({}) differ from the trait declaration ({})",
+				found.join(", "),
+				declared.join(", ")
+			),
+			Self::ZeroPlaceholder => write!(f, "The weight is a `Weight::zero()` placeholder"),
+			Self::MissingProof =>
+				write!(f, "The weight has no proof size since it only sets the ref time"),
+		}
+	}
+}
+
+/// Lints the components of a weight function.
+///
+/// `params` are the parameter names of the impl fn and `declared` those of the trait fn, if the
+/// file contains the trait.
+pub fn lint_components(
+	term: &ChromaticTerm,
+	comp_ranges: Option<&ComponentRanges>,
+	params: &[ComponentName],
+	declared: Option<&[ComponentName]>,
+) -> Vec<Lint> {
+	let mut lints = Vec::new();
+	let vars = term.free_vars(&Scope::empty());
+
+	for param in params {
+		let component = param.trim_start_matches('_');
+		if !param.starts_with('_') && !vars.contains(component) {
+			lints.push(Lint::UnusedComponent { component: component.into() });
+		}
+		// The parser drops the underscores of the range annotations.
+		let has_range = comp_ranges.is_some_and(|r| r.contains_key(&component.replace('_', "")));
+		if !has_range {
+			lints.push(Lint::MissingRange { component: component.into() });
+		}
+	}
+
+	let constants = SimpleScope::from_substrate().as_vec();
+	let known = params
+		.iter()
+		.map(|p| p.trim_start_matches('_'))
+		.chain([STORAGE_READ_VAR, STORAGE_WRITE_VAR])
+		.chain(constants.iter().map(|(var, _)| var.as_str()))
+		.collect::<BTreeSet<_>>();
+	for var in vars {
+		if !known.contains(var.as_str()) {
+			lints.push(Lint::UndeclaredComponent { component: var });
+		}
+	}
+
+	if let Some(declared) = declared {
+		let strip = |names: &[ComponentName]| {
+			names.iter().map(|n| n.trim_start_matches('_').to_string()).collect::<Vec<_>>()
+		};
+		if strip(declared) != strip(params) {
+			lints.push(Lint::SignatureMismatch {
+				declared: declared.to_vec(),
+				found: params.to_vec(),
+			});
+		}
+	}
+	lints
+}
+
+/// A check of [`lint_files`].
+#[derive(
+	Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+	/// The file is not a weight file or could not be parsed.
+	ParseError,
+	/// The weight is zero for all components.
+	ZeroWeight,
+	/// See [`Lint::ZeroPlaceholder`].
+	ZeroPlaceholder,
+	/// See [`Lint::MissingProof`].
+	MissingProof,
+	/// See [`Lint::MissingRange`].
+	MissingRange,
+	/// A component has the same minimum and maximum.
+	ConstantRange,
+	/// A component adds more than [`LintParams::max_slope`] time per unit.
+	HighSlope,
+	/// More than 1000 storage reads or writes, see [`crate::sanity_check_term`].
+	ExcessiveStorage,
+	/// The same extrinsic of the same pallet is in multiple files of one directory.
+	DuplicateExtrinsic,
+	/// See [`Lint::UnusedComponent`].
+	UnusedComponent,
+	/// See [`Lint::UndeclaredComponent`].
+	UndeclaredComponent,
+	/// See [`Lint::SignatureMismatch`].
+	SignatureMismatch,
+}
+
+impl Rule {
+	/// All rules in the order of their declaration.
+	pub fn all() -> Vec<Self> {
+		<Self as clap::ValueEnum>::value_variants().to_vec()
+	}
+
+	/// Kebab-case ID of the rule, for example `zero-weight`.
+	pub fn id(&self) -> String {
+		clap::ValueEnum::to_possible_value(self)
+			.expect("No variant is skipped")
+			.get_name()
+			.into()
+	}
+
+	pub fn severity(&self) -> Severity {
+		match self {
+			Self::ParseError |
+			Self::ZeroWeight |
+			Self::ZeroPlaceholder |
+			Self::DuplicateExtrinsic |
+			Self::UndeclaredComponent |
+			Self::SignatureMismatch => Severity::Error,
+			Self::MissingProof |
+			Self::MissingRange |
+			Self::HighSlope |
+			Self::ExcessiveStorage |
+			Self::UnusedComponent => Severity::Warning,
+			Self::ConstantRange => Severity::Note,
+		}
+	}
+
+	/// One sentence that explains the rule.
+	pub fn description(&self) -> &'static str {
+		match self {
+			Self::ParseError => "The file is not a weight file or could not be parsed",
+			Self::ZeroWeight => "The weight is zero for all components",
+			Self::ZeroPlaceholder => "The weight is a `Weight::zero()` placeholder",
+			Self::MissingProof => "The weight has no proof size",
+			Self::MissingRange => "A component has no range annotation",
+			Self::ConstantRange => "A component has the same minimum and maximum",
+			Self::HighSlope => "A component adds an abnormally high weight per unit",
+			Self::ExcessiveStorage => "The weight has more than 1000 storage reads or writes",
+			Self::DuplicateExtrinsic => "The extrinsic is in multiple files",
+			Self::UnusedComponent => "A component is not used in the weight term",
+			Self::UndeclaredComponent => "A variable of the weight term is not a parameter",
+			Self::SignatureMismatch => "The parameters differ from the trait declaration",
+		}
+	}
+}
+
+impl From<&Lint> for Rule {
+	fn from(lint: &Lint) -> Self {
+		match lint {
+			Lint::UnusedComponent { .. } => Self::UnusedComponent,
+			Lint::UndeclaredComponent { .. } => Self::UndeclaredComponent,
+			Lint::MissingRange { .. } => Self::MissingRange,
+			Lint::SignatureMismatch { .. } => Self::SignatureMismatch,
+			Lint::ZeroPlaceholder => Self::ZeroPlaceholder,
+			Lint::MissingProof => Self::MissingProof,
+		}
+	}
+}
+
+impl std::fmt::Display for Rule {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}", self.id())
+	}
+}
+
+/// How bad a [`Finding`] is. Named like the levels of SARIF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+	Error,
+	Warning,
+	Note,
+}
+
+impl std::fmt::Display for Severity {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::Error => write!(f, "error"),
+			Self::Warning => write!(f, "warning"),
+			Self::Note => write!(f, "note"),
+		}
+	}
+}
+
+/// A violation of a [`Rule`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct Finding {
+	pub rule: Rule,
+	pub severity: Severity,
+	pub file: String,
+	/// Line of the weight function or parse error, starting at 1.
+	pub line: Option<usize>,
+	/// `None` if the finding is about the whole file.
+	pub extrinsic: Option<ExtrinsicName>,
+	pub message: String,
+}
+
+/// Ignores the findings of a rule, either everywhere or only for some extrinsics.
+///
+/// Parses from `RULE` or `RULE:REGEX`, for example `missing-range:pallet_staking.rs::bond`. The
+/// regex is matched against `<FILE NAME>::<EXTRINSIC>`.
+#[derive(Clone, Debug)]
+pub struct Allow {
+	pub rule: Rule,
+	pub extrinsic: Option<Regex>,
+}
+
+impl PartialEq for Allow {
+	fn eq(&self, other: &Self) -> bool {
+		self.rule == other.rule &&
+			self.extrinsic.as_ref().map(Regex::as_str) ==
+				other.extrinsic.as_ref().map(Regex::as_str)
+	}
+}
+
+impl Eq for Allow {}
+
+impl Allow {
+	fn allows(&self, finding: &Finding) -> bool {
+		if self.rule != finding.rule {
+			return false
+		}
+		let Some(re) = &self.extrinsic else { return true };
+		let Some(extrinsic) = &finding.extrinsic else { return false };
+
+		let pallet = PathStripping::FileName.strip(Path::new("."), Path::new(&finding.file));
+		re.is_match(&format!("{}::{}", pallet, extrinsic)).unwrap_or_default()
+	}
+}
+
+impl std::str::FromStr for Allow {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, String> {
+		let (rule, extrinsic) = match s.split_once(':') {
+			Some((rule, extrinsic)) => (rule, Some(extrinsic)),
+			None => (s, None),
+		};
+		let rule = <Rule as clap::ValueEnum>::from_str(rule, true)
+			.map_err(|_| format!("Unknown rule: {}", rule))?;
+		let extrinsic = extrinsic
+			.map(Regex::new)
+			.transpose()
+			.map_err(|e| format!("Invalid allow regex: {}", e))?;
+		Ok(Self { rule, extrinsic })
+	}
+}
+
+/// Default of [`LintParams::max_slope`]: 10ms per unit of a component.
+pub const DEFAULT_MAX_SLOPE: u128 = 10_000_000_000;
+
+/// Parameters of [`lint_files`].
+#[derive(Debug, Clone, PartialEq, Eq, clap::Args)]
+pub struct LintParams {
+	/// Ignore the findings of a rule: `RULE` or `RULE:REGEX`.
+	///
+	/// The regex is matched against `<FILE NAME>::<EXTRINSIC>`, for example
+	/// `missing-range:pallet_staking.rs::bond`. Can be repeated.
+	#[clap(long, value_name = "RULE[:REGEX]", action = clap::ArgAction::Append)]
+	pub allow: Vec<Allow>,
+
+	/// File with one `--allow` entry per line. Empty lines and lines starting with `#` are
+	/// ignored.
+	#[clap(long, value_name = "FILE")]
+	pub allow_file: Option<PathBuf>,
+
+	/// Time in pico seconds per unit of a component above which `high-slope` is reported.
+	#[clap(long, value_name = "PICOS", default_value_t = DEFAULT_MAX_SLOPE)]
+	pub max_slope: u128,
+}
+
+impl Default for LintParams {
+	fn default() -> Self {
+		Self { allow: Vec::new(), allow_file: None, max_slope: DEFAULT_MAX_SLOPE }
+	}
+}
+
+impl LintParams {
+	/// The `--allow` entries and those of the `--allow-file`.
+	pub fn allowed(&self) -> Result<Vec<Allow>, Error> {
+		let mut allowed = self.allow.clone();
+		if let Some(path) = &self.allow_file {
+			let content = read_file(path)?;
+			for line in content.lines().map(str::trim) {
+				if line.is_empty() || line.starts_with('#') {
+					continue
+				}
+				let allow = line.parse().map_err(|msg| Error::InvalidParam { msg })?;
+				allowed.push(allow);
+			}
+		}
+		Ok(allowed)
+	}
+}
+
+/// Lints weight files and returns the findings that are not allowed.
+///
+/// The findings are sorted by file and line.
+pub fn lint_files(
+	files: &[PathBuf],
+	impls: &ImplParams,
+	params: &LintParams,
+) -> Result<Vec<Finding>, Error> {
+	let allowed = params.allowed()?;
+	let mut findings = Vec::new();
+	// The first file of each pallet and extrinsic per directory. Different directories are
+	// different versions or runtimes and can have the same extrinsics.
+	let mut seen = HashMap::<(PathBuf, String, ExtrinsicName), String>::new();
+
+	for path in files {
+		let file = path.display().to_string();
+		let (report, extrinsics) = report_file(path, impls);
+		if let Some(error) = report.error {
+			findings.push(Finding {
+				rule: Rule::ParseError,
+				severity: Rule::ParseError.severity(),
+				file: file.clone(),
+				line: error_line(&error),
+				extrinsic: None,
+				message: error.to_string(),
+			});
+			continue
+		}
+		let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+		let dir = canonical.parent().map(Path::to_path_buf).unwrap_or_default();
+
+		for ext in extrinsics.iter().filter(|e| e.kind == WeightKind::Pallet) {
+			let mut found = Vec::new();
+			lint_extrinsic(ext, params, &mut found);
+			let key = (dir.clone(), pallet_name(ext, path), ext.name.clone());
+			match seen.get(&key) {
+				Some(first) => found.push((
+					Rule::DuplicateExtrinsic,
+					format!("Extrinsic '{}' is also in {}", ext.name, first),
+				)),
+				None => {
+					seen.insert(key, file.clone());
+				},
+			}
+
+			let line = ext.line;
+			findings.extend(found.into_iter().map(|(rule, message)| Finding {
+				rule,
+				severity: rule.severity(),
+				file: file.clone(),
+				line,
+				extrinsic: Some(ext.name.clone()),
+				message,
+			}));
+		}
+	}
+
+	let mut kept = findings
+		.into_iter()
+		.filter(|finding| !allowed.iter().any(|allow| allow.allows(finding)))
+		.collect::<Vec<_>>();
+	kept.sort_by(|a, b| (&a.file, a.line, a.rule).cmp(&(&b.file, b.line, b.rule)));
+	Ok(kept)
+}
+
+/// The pallet of the benchmark header or else the file name without extensions.
+fn pallet_name(ext: &ChromaticExtrinsic, path: &Path) -> String {
+	ext.metadata.as_ref().and_then(|m| m.pallet.clone()).unwrap_or_else(|| {
+		let name = path.file_name().unwrap_or_default().to_string_lossy();
+		name.split('.').next().unwrap_or_default().to_string()
+	})
+}
+
+/// Checks a single extrinsic against all rules that do not need other extrinsics.
+fn lint_extrinsic(ext: &ChromaticExtrinsic, params: &LintParams, found: &mut Vec<(Rule, String)>) {
+	found.extend(ext.lints.iter().map(|lint| (Rule::from(lint), lint.to_string())));
+
+	let time = ext.term.simplify(Dimension::Time).ok().and_then(|t| t.coefficients());
+	let proof = ext.term.simplify(Dimension::Proof).ok().and_then(|t| t.coefficients());
+	let placeholder = ext.lints.contains(&Lint::ZeroPlaceholder);
+	if let (Some(time), Some(proof)) = (&time, &proof) {
+		if time.is_empty() && proof.is_empty() && !placeholder {
+			found.push((Rule::ZeroWeight, "The weight is zero for all components".into()));
+		}
+	}
+
+	for (name, range) in ext.comp_ranges.iter().flatten().collect::<BTreeMap<_, _>>() {
+		if range.min == range.max {
+			found.push((
+				Rule::ConstantRange,
+				format!(
+					"Component '{}' has the constant range [{}, {}]",
+					name, range.min, range.max
+				),
+			));
+		}
+	}
+
+	for (monomial, slope) in time.iter().flatten() {
+		let storage =
+			monomial.vars().iter().any(|v| v == STORAGE_READ_VAR || v == STORAGE_WRITE_VAR);
+		if !monomial.is_constant() && !storage && *slope > params.max_slope {
+			found.push((
+				Rule::HighSlope,
+				format!(
+					"Component '{}' adds {} per unit which is more than {}",
+					monomial,
+					Dimension::fmt_time(*slope),
+					Dimension::fmt_time(params.max_slope)
+				),
+			));
+		}
+	}
+
+	if let Ok(Err(msg)) = ext.term.simplify(Dimension::Time).map(|t| sanity_check_term(&t)) {
+		found.push((Rule::ExcessiveStorage, msg));
+	}
+}
+
+/// The line of the error or of the first rejection that has one.
+fn error_line(error: &Error) -> Option<usize> {
+	match error {
+		Error::Parse { span, .. } | Error::UnknownCall { span, .. } => span.map(|s| s.line),
+		Error::UnknownFile { rejections, .. } =>
+			rejections.iter().find_map(|r| error_line(&r.error)),
+		_ => None,
+	}
+}
@@ -0,0 +1,121 @@
+//! Parses the header that the benchmarking CLI puts into each weight file.
+//!
+//! Example:
+//!
+//! ```text
+//! //! Autogenerated weights for pallet_staking
+//! //!
+//! //! DATE: 2023-01-25, STEPS: `50`, REPEAT: `20`, LOW RANGE: `[]`, HIGH RANGE: `[]`
+//! //! WORST CASE MAP SIZE: `1000000`
+//! //! HOSTNAME: `bm2`, CPU: `Intel(R) Core(TM) i7-7700K CPU @ 4.20GHz`
+//! //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//!
+//! // Executed Command:
+//! // ./target/production/substrate
+//! // benchmark
+//! // pallet
+//! ```
+
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+/// Metadata about how a weight file was generated.
+///
+/// All fields are optional since older versions of the benchmarking CLI emit less of them.
+#[derive(Clone, Debug, PartialEq, Eq, Default, serde::Serialize)]
+pub struct BenchmarkMetadata {
+	/// The pallet of the `Autogenerated weights for` line.
+	pub pallet: Option<String>,
+	pub date: Option<String>,
+	pub steps: Option<u32>,
+	pub repeat: Option<u32>,
+	pub worst_case_map_size: Option<u32>,
+	pub hostname: Option<String>,
+	pub cpu: Option<String>,
+	pub chain: Option<String>,
+	/// The `// Executed Command:` block; one line per argument.
+	pub command: Vec<String>,
+}
+
+impl BenchmarkMetadata {
+	pub fn is_empty(&self) -> bool {
+		self == &Self::default()
+	}
+
+	/// Explains why the weights of `self` and `new` should not be compared.
+	///
+	/// This is the case if they were generated on a different machine, with fewer steps or
+	/// repeats or a different worst case map size.
+	pub fn compare(&self, new: &Self) -> Vec<String> {
+		let mut warnings = Vec::new();
+		let mut different = |what: &str, old: &Option<String>, new: &Option<String>| {
+			if let (Some(old), Some(new)) = (old, new) {
+				if old != new {
+					warnings.push(format!("Different {}: `{}` vs `{}`", what, old, new));
+				}
+			}
+		};
+		different("CPU", &self.cpu, &new.cpu);
+		different("hostname", &self.hostname, &new.hostname);
+		let to_string = |v: Option<u32>| v.map(|v| v.to_string());
+		different(
+			"worst case map size",
+			&to_string(self.worst_case_map_size),
+			&to_string(new.worst_case_map_size),
+		);
+
+		let mut fewer = |what: &str, old: Option<u32>, new: Option<u32>| {
+			if let (Some(old), Some(new)) = (old, new) {
+				if new < old {
+					warnings.push(format!("Fewer {}: {} vs {}", what, old, new));
+				}
+			}
+		};
+		fewer("steps", self.steps, new.steps);
+		fewer("repeats", self.repeat, new.repeat);
+
+		warnings
+	}
+}
+
+/// Parses the metadata from the content of a weight file.
+///
+/// Returns `None` if no metadata was found.
+pub fn parse_content(content: &str) -> Option<BenchmarkMetadata> {
+	lazy_static! {
+		static ref PAIR: Regex =
+			Regex::new(r#"(?P<key>[A-Z][A-Z -]*[A-Z]):\s*(?P<value>`[^`]*`|[^,]*)"#).unwrap();
+	}
+
+	let mut res = BenchmarkMetadata::default();
+	let mut lines = content.lines().map(str::trim);
+	while let Some(line) = lines.next() {
+		if let Some(header) = line.strip_prefix("//!") {
+			if let Some(pallet) = header.trim().strip_prefix("Autogenerated weights for ") {
+				res.pallet = Some(pallet.trim().trim_matches('`').into());
+			}
+			for caps in PAIR.captures_iter(header).filter_map(Result::ok) {
+				let value = caps["value"].trim().trim_matches('`').to_string();
+				match &caps["key"] {
+					"DATE" => res.date = Some(value),
+					"STEPS" => res.steps = value.parse().ok(),
+					"REPEAT" => res.repeat = value.parse().ok(),
+					"WORST CASE MAP SIZE" => res.worst_case_map_size = value.parse().ok(),
+					"HOSTNAME" => res.hostname = Some(value),
+					"CPU" => res.cpu = Some(value),
+					"CHAIN" => res.chain = Some(value),
+					_ => {},
+				}
+			}
+		} else if line == "// Executed Command:" {
+			res.command = lines
+				.by_ref()
+				.map_while(|l| l.strip_prefix("//"))
+				.map(|l| l.trim().to_string())
+				.take_while(|l| !l.is_empty())
+				.collect();
+		}
+	}
+
+	(!res.is_empty()).then_some(res)
+}
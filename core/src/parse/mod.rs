@@ -6,20 +6,85 @@
 //! - Extrinsic Base weight (often extrinsic_weight.rs)
 //! - Block Execution weight (often block_weight.rs)
 //!
-//! Each module corresponds to one of these categories.
+//! Each module corresponds to one of these categories. The `metadata` module parses the header
+//! that all of them share.
 
+pub mod metadata;
 pub mod overhead;
 pub mod pallet;
 pub mod storage;
 
-use std::{io::Read, path::Path};
+use std::{
+	io::Read,
+	path::{Path, PathBuf},
+};
 
+use crate::{
+	error::Rejection,
+	scope::SimpleScope,
+	term::{ChromaticTerm, SimpleTerm, Term},
+	Dimension, Error, PalletName,
+};
+use pallet::{ChromaticExtrinsic, ImplParams, WeightKind};
+
+#[derive(Debug, Clone)]
 pub enum ParsedFile {
 	Pallet(Vec<pallet::ChromaticExtrinsic>),
 	Storage(storage::Weights),
 	Overhead(overhead::Weight),
 }
 
+impl ParsedFile {
+	pub fn kind(&self) -> WeightKind {
+		match self {
+			Self::Pallet(_) => WeightKind::Pallet,
+			Self::Storage(_) => WeightKind::Storage,
+			Self::Overhead(_) => WeightKind::Overhead,
+		}
+	}
+
+	/// Converts the file into extrinsics so that all kinds of files can be compared alike.
+	///
+	/// A storage weight file becomes a `read` and a `write` extrinsic and an overhead weight file
+	/// an extrinsic that is named like its constant, for example `ExtrinsicBaseWeight`.
+	pub fn into_extrinsics(self, pallet: PalletName) -> Vec<ChromaticExtrinsic> {
+		let ext = |name: &str, term: ChromaticTerm, kind: WeightKind| ChromaticExtrinsic {
+			name: name.into(),
+			pallet: pallet.clone(),
+			term: substitute_constants(term),
+			comp_ranges: None,
+			storage: Vec::new(),
+			summary: Default::default(),
+			metadata: None,
+			kind,
+			lints: Vec::new(),
+			line: None,
+		};
+
+		match self {
+			Self::Pallet(extrinsics) => extrinsics,
+			Self::Storage(storage::Weights { weights, .. }) => vec![
+				ext("read", weights.read.into_chromatic(Dimension::Time), WeightKind::Storage),
+				ext("write", weights.write.into_chromatic(Dimension::Time), WeightKind::Storage),
+			],
+			Self::Overhead(overhead::Weight::BlockExecution(term)) =>
+				vec![ext("BlockExecutionWeight", term, WeightKind::Overhead)],
+			Self::Overhead(overhead::Weight::ExtrinsicBase(term)) =>
+				vec![ext("ExtrinsicBaseWeight", term, WeightKind::Overhead)],
+		}
+	}
+}
+
+/// Replaces the Substrate constants like `WEIGHT_PER_NANOS` with their values.
+pub(crate) fn substitute_constants(mut term: ChromaticTerm) -> ChromaticTerm {
+	for (var, value) in SimpleScope::from_substrate().as_vec() {
+		if let SimpleTerm::Scalar(value) = value {
+			term.substitute(&var, &Term::Scalar(value));
+		}
+	}
+	term
+}
+
 /// Defines how a path is transformed into a pallet name.
 ///
 /// Take the following example:
@@ -67,32 +132,173 @@ impl std::str::FromStr for PathStripping {
 	}
 }
 
-/// Tries to guess the type of weight file and parses it.
+/// Detects the kind of a weight file and parses it.
 ///
-/// Does not return an error since it just *tires* to do so, not guarantee.
-pub fn try_parse_file(repo: &Path, file: &Path) -> Option<ParsedFile> {
-	if let Ok(parsed) = pallet::parse_file_in_repo(repo, file) {
-		return Some(ParsedFile::Pallet(parsed))
+/// The pallet, storage and overhead parsers are tried in this order. If all of them reject the
+/// file, the error contains the reason of each one, see [`Error::UnknownFile`].
+pub fn try_parse_file(repo: &Path, file: &Path, impls: &ImplParams) -> Result<ParsedFile, Error> {
+	let content = read_file(file)?;
+	classify(PathStripping::RepoRelative.strip(repo, file), file, &content, impls)
+}
+
+fn classify(
+	pallet: PalletName,
+	file: &Path,
+	content: &str,
+	impls: &ImplParams,
+) -> Result<ParsedFile, Error> {
+	let reject =
+		|parser: WeightKind, error: Error| Rejection { parser, error: error.in_file(file) };
+
+	let pallet = match pallet::parse_content(pallet, content.into(), impls) {
+		Ok(parsed) => return Ok(ParsedFile::Pallet(parsed)),
+		Err(e) => reject(WeightKind::Pallet, e),
+	};
+	let storage = match storage::parse_content(content.into()) {
+		Ok(parsed) => return Ok(ParsedFile::Storage(parsed)),
+		Err(e) => reject(WeightKind::Storage, e),
+	};
+	let overhead = match overhead::parse_content(content.into()) {
+		Ok(parsed) => return Ok(ParsedFile::Overhead(parsed)),
+		Err(e) => reject(WeightKind::Overhead, e),
+	};
+
+	Err(Error::UnknownFile {
+		file: file.display().to_string(),
+		rejections: vec![pallet, storage, overhead],
+	})
+}
+
+/// What parsing a single file found, see [`report_file`].
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FileReport {
+	pub file: String,
+	/// The detected kind of the file or `None` if it could not be parsed.
+	pub kind: Option<WeightKind>,
+	/// Number of extrinsics in the file, see [`ParsedFile::into_extrinsics`].
+	pub extrinsics: usize,
+	pub warnings: Vec<String>,
+	/// Number of [`crate::lint::Lint`]s of all extrinsics.
+	pub lints: usize,
+	pub error: Option<Error>,
+}
+
+/// Parses a file and reports its kind and problems instead of failing.
+///
+/// Also returns the extrinsics of the file, named like in [`parse_file`].
+pub fn report_file(file: &Path, impls: &ImplParams) -> (FileReport, Vec<ChromaticExtrinsic>) {
+	let mut report = FileReport {
+		file: file.display().to_string(),
+		kind: None,
+		extrinsics: 0,
+		warnings: Vec::new(),
+		lints: 0,
+		error: None,
+	};
+	let pallet = PathStripping::FileName.strip(Path::new("."), file);
+	let parsed =
+		read_file(file).and_then(|content| classify(pallet.clone(), file, &content, impls));
+	let parsed = match parsed {
+		Ok(parsed) => parsed,
+		Err(e) => {
+			report.error = Some(e);
+			return (report, Vec::new())
+		},
+	};
+
+	report.kind = Some(parsed.kind());
+	let extrinsics = parsed.into_extrinsics(pallet);
+	report.extrinsics = extrinsics.len();
+	report.lints = extrinsics.iter().map(|e| e.lints.len()).sum();
+	for ext in &extrinsics {
+		let checked = ext.term.simplify(Dimension::Time).map(|t| crate::sanity_check_term(&t));
+		if let Ok(Err(warning)) = checked {
+			report.warnings.push(format!("{}: {}", ext.name, warning));
+		}
 	}
-	if let Ok(parsed) = storage::parse_file(file) {
-		return Some(ParsedFile::Storage(parsed))
+	(report, extrinsics)
+}
+
+/// Parses a pallet, storage or overhead weight file into extrinsics.
+///
+/// See [`ParsedFile::into_extrinsics`] and [`try_parse_file`].
+pub fn parse_file_in_repo(
+	repo: &Path,
+	file: &Path,
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>, Error> {
+	parse_any(PathStripping::RepoRelative.strip(repo, file), file, impls)
+}
+
+/// Same as [`parse_file_in_repo`] but names the pallet after the file name.
+pub fn parse_file(file: &Path, impls: &ImplParams) -> Result<Vec<ChromaticExtrinsic>, Error> {
+	parse_any(PathStripping::FileName.strip(Path::new("."), file), file, impls)
+}
+
+pub fn parse_files_in_repo(
+	repo: &Path,
+	paths: &[PathBuf],
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>, Error> {
+	let mut res = Vec::new();
+	for path in paths {
+		res.extend(parse_file_in_repo(repo, path, impls)?);
 	}
-	if let Ok(parsed) = overhead::parse_file(file) {
-		return Some(ParsedFile::Overhead(parsed))
+	Ok(res)
+}
+
+pub fn parse_files(
+	paths: &[PathBuf],
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>, Error> {
+	let mut res = Vec::new();
+	for path in paths {
+		res.extend(parse_file(path, impls)?);
 	}
+	Ok(res)
+}
+
+pub fn try_parse_files_in_repo(
+	repo: &Path,
+	paths: &[PathBuf],
+	impls: &ImplParams,
+) -> Vec<ChromaticExtrinsic> {
+	paths
+		.iter()
+		.filter_map(|p| parse_file_in_repo(repo, p, impls).ok())
+		.flatten()
+		.collect()
+}
+
+pub fn try_parse_files(paths: &[PathBuf], impls: &ImplParams) -> Vec<ChromaticExtrinsic> {
+	paths.iter().filter_map(|p| parse_file(p, impls).ok()).flatten().collect()
+}
 
-	None
+fn parse_any(
+	pallet: PalletName,
+	file: &Path,
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>, Error> {
+	let content = read_file(file)?;
+	let parsed = match classify(pallet.clone(), file, &content, impls)? {
+		ParsedFile::Pallet(extrinsics) => return Ok(extrinsics),
+		parsed => parsed,
+	};
+
+	let metadata = metadata::parse_content(&content);
+	let mut extrinsics = parsed.into_extrinsics(pallet);
+	for ext in extrinsics.iter_mut() {
+		ext.metadata = metadata.clone();
+	}
+	Ok(extrinsics)
 }
 
-pub fn read_file(file: &Path) -> Result<String, String> {
-	let mut raw = std::fs::File::options()
-		.read(true)
-		.write(false)
-		.open(file)
-		.map_err(|e| format!("{}: {:?}", file.display(), e))?;
+pub fn read_file(file: &Path) -> Result<String, Error> {
+	let io_err =
+		|e: std::io::Error| Error::Io { file: file.display().to_string(), msg: format!("{:?}", e) };
+	let mut raw = std::fs::File::options().read(true).write(false).open(file).map_err(io_err)?;
 	let mut content = String::new();
-	raw.read_to_string(&mut content)
-		.map_err(|e| format!("{}: {:?}", file.display(), e))?;
+	raw.read_to_string(&mut content).map_err(io_err)?;
 	Ok(content)
 }
 
@@ -1,4 +1,4 @@
-use syn::ItemConst;
+use syn::{spanned::Spanned, ItemConst};
 
 use crate::{
 	parse::path_to_string,
@@ -12,22 +12,23 @@ pub enum Weight {
 	ExtrinsicBase(ChromaticTerm),
 }
 
-pub fn parse_file(file: &Path) -> Result<Weight, String> {
+pub fn parse_file(file: &Path) -> Result<Weight, Error> {
 	let content = super::read_file(file)?;
-	parse_content(content)
+	parse_content(content).map_err(|e| e.in_file(file))
 }
 
-pub fn parse_content(content: String) -> Result<Weight, String> {
-	let ast = syn::parse_file(&content).map_err(|e| e.to_string())?;
+pub fn parse_content(content: String) -> Result<Weight, Error> {
+	let ast = syn::parse_file(&content)
+		.map_err(|e| Error::at(e.span(), format!("syn refused to parse content: {}", e)))?;
 	for item in ast.items {
 		if let Ok(res) = handle_item(&item) {
 			return Ok(res)
 		}
 	}
-	Err("No Overhead weights found".to_string())
+	Err(Error::parse("No Overhead weights found"))
 }
 
-fn handle_item(item: &Item) -> Result<Weight, String> {
+fn handle_item(item: &Item) -> Result<Weight, Error> {
 	match item {
 		// The current Substrate template has a useless `constants` mod.
 		Item::Mod(m) => {
@@ -40,20 +41,20 @@ fn handle_item(item: &Item) -> Result<Weight, String> {
 							return res
 						}
 					}
-					return Err("Did not find parameter_types!".into())
+					return Err(Error::at(m.ident.span(), "Did not find parameter_types!"))
 				}
 			}
-			Err(format!("Unexpected module: {}", m.ident))
+			Err(Error::at(m.ident.span(), format!("Unexpected module: {}", m.ident)))
 		},
 		Item::Macro(m) => {
 			let name = m.mac.path.segments.last();
 			if name.unwrap().ident == "parameter_types" {
 				parse_macro(m.mac.tokens.clone())
 			} else {
-				Err("Unexpected macro def".into())
+				Err(Error::at(m.mac.path.span(), "Unexpected macro def"))
 			}
 		},
-		_ => Err("Could not find overhead weight in the file".into()),
+		_ => Err(Error::at(item.span(), "Could not find overhead weight in the file")),
 	}
 }
 
@@ -63,13 +64,13 @@ fn handle_item(item: &Item) -> Result<Weight, String> {
 /// ```nocompile
 /// pub const BlockExecutionWeight: Weight = 5_481_991 * WEIGHT_PER_NANOS;
 /// ```
-fn parse_macro(tokens: proc_macro2::TokenStream) -> Result<Weight, String> {
-	let def: ItemConst = syn::parse2(tokens).map_err(|e| e.to_string())?;
+fn parse_macro(tokens: proc_macro2::TokenStream) -> Result<Weight, Error> {
+	let def: ItemConst = syn::parse2(tokens).map_err(|e| Error::at(e.span(), e.to_string()))?;
 	let name = def.ident.to_string();
 
 	let type_name = type_to_string(&def.ty, None)?;
 	if type_name != "Weight" {
-		return Err(format!("Unexpected const type: {}", type_name))
+		return Err(Error::at(def.ty.span(), format!("Unexpected const type: {}", type_name)))
 	}
 	let weight: ChromaticTerm = match def.expr.as_ref() {
 		Expr::Binary(bin) => {
@@ -82,15 +83,15 @@ fn parse_macro(tokens: proc_macro2::TokenStream) -> Result<Weight, String> {
 	match name.as_str() {
 		"BlockExecutionWeight" => Ok(Weight::BlockExecution(weight)),
 		"ExtrinsicBaseWeight" => Ok(Weight::ExtrinsicBase(weight)),
-		_ => Err(format!("Unexpected const name: {}", name)),
+		_ => Err(Error::at(def.ident.span(), format!("Unexpected const name: {}", name))),
 	}
 }
 
 /// Expects a path to a type and returns the type name.
-fn type_to_string(p: &syn::Type, delimiter: Option<&str>) -> Result<String, String> {
+fn type_to_string(p: &syn::Type, delimiter: Option<&str>) -> Result<String, Error> {
 	if let Type::Path(p) = p {
 		Ok(path_to_string(&p.path, delimiter))
 	} else {
-		Err("Unexpected type".into())
+		Err(Error::at(p.span(), "Unexpected type"))
 	}
 }
@@ -2,7 +2,7 @@ use crate::{
 	creads, cwrites, reads,
 	term::{SimpleTerm, Term},
 	traits::*,
-	writes, Dimension, ExtrinsicName, PalletName,
+	writes, Dimension, Error, ExtrinsicName, PalletName, ValueChange,
 };
 
 use fancy_regex::Regex;
@@ -12,27 +12,139 @@ use std::{
 	path::{Path, PathBuf},
 };
 use syn::{
-	punctuated::Punctuated, Attribute, Expr, ExprCall, ExprMethodCall, ImplItem, ImplItemFn, Item,
-	Lit, ReturnType, Stmt, Token, Type, __private::ToTokens,
+	punctuated::Punctuated, spanned::Spanned, Attribute, Expr, ExprCall, ExprMethodCall, FnArg,
+	ImplItem, ImplItemFn, Item, ItemImpl, Lit, Pat, ReturnType, Signature, Stmt, Token, TraitItem,
+	Type, __private::ToTokens,
 };
 
 use crate::{
-	parse::{path_to_string, PathStripping},
+	lint::{lint_components, Lint},
+	parse::{metadata::BenchmarkMetadata, path_to_string, PathStripping},
 	term::ChromaticTerm,
 };
 
-pub type Result<T> = std::result::Result<T, String>;
+pub type Result<T> = std::result::Result<T, Error>;
 
 pub type ComponentName = String;
 
 /// Inclusive range of a component.
-#[derive(Clone, Debug, PartialEq, Eq, Copy)]
+#[derive(Clone, Debug, PartialEq, Eq, Copy, serde::Serialize)]
 pub struct ComponentRange {
 	pub min: u32,
 	pub max: u32,
 }
 pub type ComponentRanges = HashMap<ComponentName, ComponentRange>;
 
+/// Selects the trait impl of a pallet weight file that is parsed.
+///
+/// A weight file can implement the weight trait for multiple types, for example for
+/// `SubstrateWeight<T>` and `()`. Only the impl whose type comes first in [`Self::impl_names`]
+/// is parsed. Ties are broken by the order in the file.
+#[derive(Debug, Clone, PartialEq, Eq, clap::Args)]
+pub struct ImplParams {
+	/// Types that implement the weight trait, most preferred first.
+	///
+	/// Either the name of the type like `SubstrateWeight` or its full path like
+	/// `pallet_xyz::weights::SubstrateWeight`. `()` matches the unit type and `*` any
+	/// implementor of a `*WeightInfo` trait. Can be repeated or comma separated.
+	#[clap(
+		long = "impl-name",
+		value_name = "NAME",
+		action = clap::ArgAction::Append,
+		value_delimiter = ',',
+		default_values_t = ImplParams::default().impl_names,
+	)]
+	pub impl_names: Vec<String>,
+}
+
+impl Default for ImplParams {
+	fn default() -> Self {
+		Self { impl_names: vec!["SubstrateWeight".into(), "WeightInfo".into(), "()".into()] }
+	}
+}
+
+impl ImplParams {
+	/// The position of the first name in [`Self::impl_names`] that matches the impl.
+	fn rank(&self, imp: &ItemImpl) -> Option<usize> {
+		let (name, path) = match imp.self_ty.as_ref() {
+			Type::Tuple(t) if t.elems.is_empty() => ("()".to_string(), "()".to_string()),
+			Type::Path(p) =>
+				(p.path.segments.last()?.ident.to_string(), path_to_string(&p.path, Some("::"))),
+			_ => return None,
+		};
+		let weight_trait = imp
+			.trait_
+			.as_ref()
+			.and_then(|(_, t, _)| t.segments.last())
+			.is_some_and(|s| s.ident.to_string().ends_with("WeightInfo"));
+
+		self.impl_names.iter().position(|n| match n.as_str() {
+			"*" => weight_trait,
+			n => n == name || n == path,
+		})
+	}
+}
+
+/// A storage item that an extrinsic accesses according to its doc comments.
+///
+/// These doc comments:
+/// ```text
+/// Storage: Staking Ledger (r:1 w:1)
+/// Proof: Staking Ledger (max_values: None, max_size: Some(1091), added: 3566, mode: MaxEncodedLen)
+/// ```
+/// would be parsed into:
+/// ```text
+/// StorageAccess { pallet: "Staking", item: "Ledger", reads: 1, writes: 1, max_size: Some(1091),
+///                 mode: Some("MaxEncodedLen") }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct StorageAccess {
+	/// Pallet of the storage item. Empty for unknown keys.
+	pub pallet: String,
+	/// Name of the storage item or the raw key for unknown keys.
+	pub item: String,
+	pub reads: u32,
+	pub writes: u32,
+	/// Max encoded size of the storage item from the `Proof:` comment.
+	pub max_size: Option<u32>,
+	/// Proof size estimation mode from the `Proof:` comment.
+	pub mode: Option<String>,
+}
+
+/// Values that the benchmarking CLI puts into the comments of each weight function.
+///
+/// These comments:
+///   // Proof Size summary in bytes:
+///   //  Measured:  `1079`
+///   //  Estimated: `10386`
+///   // Minimum execution time: 40_015 nanoseconds.
+/// would be parsed into:
+///   BenchmarkSummary { measured_proof: Some(1079), estimated_proof: Some(10386), min_time:
+///   Some(40_015_000) }
+#[derive(Clone, Debug, PartialEq, Eq, Default, serde::Serialize)]
+pub struct BenchmarkSummary {
+	/// Measured proof size of the benchmark in byte.
+	pub measured_proof: Option<u128>,
+	/// Estimated worst case proof size in byte.
+	pub estimated_proof: Option<u128>,
+	/// Minimum execution time of the benchmark in pico seconds.
+	pub min_time: Option<u128>,
+}
+
+impl BenchmarkSummary {
+	pub fn is_empty(&self) -> bool {
+		self == &Self::default()
+	}
+
+	/// Compares the measured with the estimated proof size.
+	///
+	/// The percent is how much larger the estimation is. Returns `None` if one of them is missing.
+	pub fn proof_estimation(&self) -> Option<ValueChange> {
+		let (measured, estimated) = self.measured_proof.zip(self.estimated_proof)?;
+		ValueChange::new(Some(measured), Some(estimated))
+	}
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GenericExtrinsic<T> {
 	pub name: ExtrinsicName,
@@ -41,6 +153,41 @@ pub struct GenericExtrinsic<T> {
 	pub term: T,
 	/// Min and max value that each weight component can have.
 	pub comp_ranges: Option<ComponentRanges>,
+	/// Storage items that are accessed as noted in the doc comments.
+	pub storage: Vec<StorageAccess>,
+	/// Values from the comments in the function body.
+	pub summary: BenchmarkSummary,
+	/// Header of the weight file that contains the extrinsic.
+	pub metadata: Option<BenchmarkMetadata>,
+	/// The kind of weight file that contains the extrinsic.
+	pub kind: WeightKind,
+	/// Problems with the components of the weight function.
+	pub lints: Vec<Lint>,
+	/// Line of the weight function in its file, starting at 1.
+	pub line: Option<usize>,
+}
+
+/// The kind of weight file that an extrinsic was parsed from, see [`super::ParsedFile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WeightKind {
+	/// A pallet weight file with one extrinsic per weight function.
+	#[default]
+	Pallet,
+	/// A storage weight file with a `read` and `write` extrinsic.
+	Storage,
+	/// An overhead weight file with a single extrinsic.
+	Overhead,
+}
+
+impl core::fmt::Display for WeightKind {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Pallet => write!(f, "pallet"),
+			Self::Storage => write!(f, "storage"),
+			Self::Overhead => write!(f, "overhead"),
+		}
+	}
 }
 
 pub type ChromaticExtrinsic = GenericExtrinsic<ChromaticTerm>;
@@ -53,123 +200,241 @@ impl<T> GenericExtrinsic<T> {
 			name: self.name,
 			pallet: self.pallet,
 			comp_ranges: self.comp_ranges,
+			storage: self.storage,
+			summary: self.summary,
+			metadata: self.metadata,
+			kind: self.kind,
+			lints: self.lints,
+			line: self.line,
 			// ..self is experimental between different types.
 		}
 	}
 }
 
-pub fn parse_file_in_repo(repo: &Path, file: &Path) -> Result<Vec<ChromaticExtrinsic>> {
+/// Parses a pallet weight file and rejects storage or overhead files.
+///
+/// Use [`super::parse_file_in_repo`] to accept all kinds of weight files.
+pub fn parse_pallet_file_in_repo(
+	repo: &Path,
+	file: &Path,
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>> {
 	let content = super::read_file(file)?;
 	let name = PathStripping::RepoRelative.strip(repo, file);
-	parse_content(name, content).map_err(|e| format!("{}: {}", file.display(), e))
+	parse_content(name, content, impls).map_err(|e| e.in_file(file))
 }
 
-pub fn parse_file(file: &Path) -> Result<Vec<ChromaticExtrinsic>> {
+/// Like [`parse_pallet_file_in_repo`] but names the pallet by its file name.
+pub fn parse_pallet_file(file: &Path, impls: &ImplParams) -> Result<Vec<ChromaticExtrinsic>> {
 	let content = super::read_file(file)?;
 	let name = PathStripping::FileName.strip(Path::new("."), file);
-	parse_content(name, content).map_err(|e| format!("{}: {}", file.display(), e))
+	parse_content(name, content, impls).map_err(|e| e.in_file(file))
 }
 
-pub fn parse_files_in_repo(repo: &Path, paths: &[PathBuf]) -> Result<Vec<ChromaticExtrinsic>> {
+pub fn parse_pallet_files_in_repo(
+	repo: &Path,
+	paths: &[PathBuf],
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>> {
 	let mut res = Vec::new();
 	for path in paths {
-		res.extend(parse_file_in_repo(repo, path)?);
+		res.extend(parse_pallet_file_in_repo(repo, path, impls)?);
 	}
 	Ok(res)
 }
 
-pub fn parse_files(paths: &[PathBuf]) -> Result<Vec<ChromaticExtrinsic>> {
+pub fn parse_pallet_files(
+	paths: &[PathBuf],
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>> {
 	let mut res = Vec::new();
 	for path in paths {
-		res.extend(parse_file(path)?);
+		res.extend(parse_pallet_file(path, impls)?);
 	}
 	Ok(res)
 }
 
-pub fn try_parse_files_in_repo(repo: &Path, paths: &[PathBuf]) -> Vec<ChromaticExtrinsic> {
+pub fn try_parse_pallet_files_in_repo(
+	repo: &Path,
+	paths: &[PathBuf],
+	impls: &ImplParams,
+) -> Vec<ChromaticExtrinsic> {
 	let mut res = Vec::new();
 	for path in paths {
-		if let Ok(parsed) = parse_file_in_repo(repo, path) {
+		if let Ok(parsed) = parse_pallet_file_in_repo(repo, path, impls) {
 			res.extend(parsed);
 		}
 	}
 	res
 }
 
-pub fn try_parse_files(paths: &[PathBuf]) -> Vec<ChromaticExtrinsic> {
+pub fn try_parse_pallet_files(paths: &[PathBuf], impls: &ImplParams) -> Vec<ChromaticExtrinsic> {
 	let mut res = Vec::new();
 	for path in paths {
-		if let Ok(parsed) = parse_file(path) {
+		if let Ok(parsed) = parse_pallet_file(path, impls) {
 			res.extend(parsed);
 		}
 	}
 	res
 }
 
-pub fn parse_content(pallet: PalletName, content: String) -> Result<Vec<ChromaticExtrinsic>> {
+pub fn parse_content(
+	pallet: PalletName,
+	content: String,
+	impls: &ImplParams,
+) -> Result<Vec<ChromaticExtrinsic>> {
 	let ast = syn::parse_file(&content)
-		.map_err(|e| format!("syn refused to parse content: {:?}: {}", content, e))?;
-	for item in ast.items {
-		if let Ok(weights) = handle_item(pallet.clone(), &item) {
-			return Ok(weights)
+		.map_err(|e| Error::at(e.span(), format!("syn refused to parse content: {}", e)))?;
+	let declared = trait_params(&ast.items);
+	let mut candidates = ast
+		.items
+		.iter()
+		.filter_map(|item| match item {
+			Item::Impl(imp) => impls.rank(imp).map(|rank| (rank, imp)),
+			_ => None,
+		})
+		.collect::<Vec<_>>();
+	// Stable, so equally ranked impls stay in the order of the file.
+	candidates.sort_by_key(|(rank, _)| *rank);
+
+	// The first error that points into the code; the others are from skipped impls.
+	let mut located = None;
+	for (_, imp) in candidates {
+		match handle_impl(pallet.clone(), imp, &declared) {
+			Ok(weights) => return Ok(with_comments(weights, imp, &content)),
+			Err(e @ Error::Parse { span: Some(_), .. }) |
+			Err(e @ Error::UnknownCall { span: Some(_), .. }) => {
+				located.get_or_insert(e);
+			},
+			Err(_) => {},
 		}
 	}
 	log::warn!("Could not find a weight implementation in {}", &pallet);
-	Err("Could not find a weight implementation in the passed file".into())
-}
-
-pub(crate) fn handle_item(pallet: PalletName, item: &Item) -> Result<Vec<ChromaticExtrinsic>> {
-	match item {
-		Item::Impl(imp) => {
-			match imp.self_ty.as_ref() {
-				// TODO handle both () and non () since ComposableFI uses ().
-				Type::Tuple(t) => {
-					if !t.elems.is_empty() {
-						// The substrate template contains the weight info twice.
-						// By skipping the not `impl ()` we ensure to parse it only once.
-						return Err("Skipped ()".into())
-					}
-				},
-				Type::Path(p) => {
-					if p.path.leading_colon.is_some() {
-						return Err("Skipped fn: impl leading color".into())
-					}
-					if p.path.segments.len() != 1 {
-						return Err("Skipped fn: impl path segment len".into())
-					}
-					if let Some(last) = p.path.segments.last() {
-						let name = last.ident.to_string();
-						if name != "WeightInfo" && name != "SubstrateWeight" {
-							return Err("Skipped fn: impl name last".into())
-						}
-					} else {
-						return Err("Skipped fn: impl name segments".into())
+	Err(located.unwrap_or_else(|| {
+		Error::parse("Could not find a weight implementation in the passed file")
+	}))
+}
+
+/// Adds the information that syn drops from the source.
+///
+/// The summaries are only searched within `imp`, since other impls have their own values.
+fn with_comments(
+	mut weights: Vec<ChromaticExtrinsic>,
+	imp: &ItemImpl,
+	content: &str,
+) -> Vec<ChromaticExtrinsic> {
+	// syn drops normal comments, so we have to look for them in the source.
+	let (start, end) = (imp.span().start().line, imp.span().end().line);
+	let lines = content.lines().skip(start.saturating_sub(1)).take(end + 1 - start);
+	let mut summaries = scan_summaries(&lines.collect::<Vec<_>>().join("\n"));
+	let metadata = super::metadata::parse_content(content);
+	for weight in weights.iter_mut() {
+		weight.summary = summaries.remove(&weight.name).unwrap_or_default();
+		weight.metadata = metadata.clone();
+	}
+	weights
+}
+
+/// The parameter names of each function in the `*WeightInfo` trait declarations.
+fn trait_params(items: &[Item]) -> HashMap<ExtrinsicName, Vec<ComponentName>> {
+	let mut res = HashMap::new();
+	for item in items {
+		match item {
+			Item::Trait(t) if t.ident.to_string().ends_with("WeightInfo") =>
+				for f in &t.items {
+					if let TraitItem::Fn(f) = f {
+						res.insert(f.sig.ident.to_string(), fn_params(&f.sig));
 					}
 				},
-				_ => return Err("Skipped fn: impl type".into()),
-			}
-			// TODO validate the trait type.
-			let mut weights = Vec::new();
-			for f in &imp.items {
-				if let ImplItem::Fn(m) = f {
-					let (ext_name, term, comp_ranges) = handle_method(m)?;
-
-					weights.push(ChromaticExtrinsic {
-						name: ext_name,
-						pallet: pallet.clone(),
-						term,
-						comp_ranges,
-					});
-				}
-			}
-			if weights.is_empty() {
-				Err("No weight functions found in trait impl".into())
-			} else {
-				Ok(weights)
-			}
-		},
-		_ => Err("No weight trait impl found".into()),
+			_ => {},
+		}
+	}
+	res
+}
+
+/// The names of the parameters of a function; `_` for a wildcard.
+fn fn_params(sig: &Signature) -> Vec<ComponentName> {
+	sig.inputs
+		.iter()
+		.filter_map(|arg| match arg {
+			FnArg::Typed(arg) => match arg.pat.as_ref() {
+				Pat::Ident(p) => Some(p.ident.to_string()),
+				p => Some(p.to_token_stream().to_string()),
+			},
+			FnArg::Receiver(_) => None,
+		})
+		.collect()
+}
+
+fn handle_impl(
+	pallet: PalletName,
+	imp: &ItemImpl,
+	declared: &HashMap<ExtrinsicName, Vec<ComponentName>>,
+) -> Result<Vec<ChromaticExtrinsic>> {
+	let mut weights = Vec::new();
+	for f in &imp.items {
+		if let ImplItem::Fn(m) = f {
+			let mut ext =
+				handle_method(pallet.clone(), m).map_err(|e| e.with_span(m.sig.ident.span()))?;
+			ext.lints.extend(lint_components(
+				&ext.term,
+				ext.comp_ranges.as_ref(),
+				&fn_params(&m.sig),
+				declared.get(&ext.name).map(Vec::as_slice),
+			));
+			weights.push(ext);
+		}
+	}
+	if weights.is_empty() {
+		Err(Error::parse("No weight functions found in trait impl"))
+	} else {
+		Ok(weights)
+	}
+}
+
+/// Scans the source for the [`BenchmarkSummary`] comments of each function.
+///
+/// The comments are attributed to the last `fn` before them. Only the first occurrence of a value
+/// counts. Pass only the source of one impl, since each impl of the file has its own comments.
+pub(crate) fn scan_summaries(content: &str) -> HashMap<ExtrinsicName, BenchmarkSummary> {
+	lazy_static! {
+		static ref FN: Regex = Regex::new(r#"^\s*(?:pub\s+)?fn\s+(?P<name>\w+)"#).unwrap();
+		static ref MEASURED: Regex =
+			Regex::new(r#"^\s*//\s*Measured:\s*`?(?P<v>[\d_]+)`?"#).unwrap();
+		static ref ESTIMATED: Regex =
+			Regex::new(r#"^\s*//\s*Estimated:\s*`?(?P<v>[\d_]+)`?"#).unwrap();
+		static ref MIN_TIME: Regex = Regex::new(
+			r#"^\s*//\s*Minimum execution time:\s*(?P<v>[\d_]+)\s*(?P<unit>picoseconds|nanoseconds)"#
+		)
+		.unwrap();
+	}
+	let value = |re: &Regex, line: &str| -> Option<(u128, String)> {
+		let caps = re.captures(line).expect("Regex is known good")?;
+		let v = caps["v"].replace('_', "").parse().ok()?;
+		Some((v, caps.name("unit").map(|u| u.as_str().to_string()).unwrap_or_default()))
+	};
+
+	let mut res = HashMap::<ExtrinsicName, BenchmarkSummary>::new();
+	let mut current = None;
+	for line in content.lines() {
+		if let Some(caps) = FN.captures(line).expect("Regex is known good") {
+			current = Some(caps["name"].to_string());
+			continue
+		}
+		let Some(name) = &current else { continue };
+		let summary = res.entry(name.clone()).or_default();
+
+		if let Some((v, _)) = value(&MEASURED, line) {
+			summary.measured_proof.get_or_insert(v);
+		} else if let Some((v, _)) = value(&ESTIMATED, line) {
+			summary.estimated_proof.get_or_insert(v);
+		} else if let Some((v, unit)) = value(&MIN_TIME, line) {
+			let v = if unit == "nanoseconds" { v.saturating_mul(1_000) } else { v };
+			summary.min_time.get_or_insert(v);
+		}
 	}
+
+	res
 }
 
 /// Parses range component attributes.
@@ -205,24 +470,27 @@ fn parse_component_attr(attr: &Attribute) -> Result<Option<(ComponentName, Compo
 	}
 	let caps = caps.unwrap();
 
-	let component = caps.name("component").ok_or("Missing component name")?.as_str();
+	let component = caps
+		.name("component")
+		.ok_or_else(|| Error::parse("Missing component name"))?
+		.as_str();
 	let min: u32 = caps
 		.name("min")
-		.ok_or("Min value not found")?
+		.ok_or_else(|| Error::parse("Min value not found"))?
 		.as_str()
 		.replace('_', "")
 		.parse()
-		.map_err(|e| format!("Could not parse min value: {:?}", e))?;
+		.map_err(|e| Error::parse(format!("Could not parse min value: {:?}", e)))?;
 	let max: u32 = caps
 		.name("max")
-		.ok_or("Max value not found")?
+		.ok_or_else(|| Error::parse("Max value not found"))?
 		.as_str()
 		.replace('_', "")
 		.parse()
-		.map_err(|e| format!("Could not parse max value: {:?}", e))?;
+		.map_err(|e| Error::parse(format!("Could not parse max value: {:?}", e)))?;
 	// Sanity check
 	if min > max {
-		return Err("Min value is greater than max value".into())
+		return Err(Error::parse("Min value is greater than max value"))
 	}
 	Ok(Some((component.into(), ComponentRange { min, max })))
 }
@@ -237,7 +505,7 @@ fn parse_component_attrs(attrs: &Vec<Attribute>) -> Result<Option<ComponentRange
 			Ok(None) => {
 				// Some kind of other attribute that we ignore.
 			},
-			Err(e) => return Err(e),
+			Err(e) => return Err(e.with_span(attr.span())),
 		}
 	}
 
@@ -248,25 +516,114 @@ fn parse_component_attrs(attrs: &Vec<Attribute>) -> Result<Option<ComponentRange
 	}
 }
 
-fn handle_method(
-	m: &ImplItemFn,
-) -> Result<(ExtrinsicName, ChromaticTerm, Option<ComponentRanges>)> {
+/// Returns the content of a doc comment attribute.
+fn doc_string(attr: &Attribute) -> Option<String> {
+	match &attr.meta {
+		syn::Meta::NameValue(syn::MetaNameValue {
+			path,
+			value: Expr::Lit(syn::ExprLit { lit: Lit::Str(s), .. }),
+			..
+		}) if path_to_string(path, None) == "doc" => Some(s.value()),
+		_ => None,
+	}
+}
+
+/// Splits a storage key into pallet and item name.
+///
+/// Understands the old `Staking Ledger` and the new `` `Staking::Ledger` `` format. Unknown keys
+/// like `` unknown `0x3a7472616e73616374696f6e5f6c6576656c3a` `` have an empty pallet.
+fn split_storage_key(key: &str) -> (String, String) {
+	let key = key.replace('`', "");
+	let key = key.trim();
+	let lower = key.to_lowercase();
+	if lower.starts_with("unknown") {
+		let raw = key.split_whitespace().last().unwrap_or_default();
+		return (String::new(), raw.trim_matches(|c| c == '[' || c == ']').into())
+	}
+
+	match key.split_once("::").or_else(|| key.split_once(' ')) {
+		Some((pallet, item)) => (pallet.trim().into(), item.trim().into()),
+		None => (key.into(), String::new()),
+	}
+}
+
+/// Parses the `Storage:` and `Proof:` doc comments of an extrinsic.
+///
+/// The `Proof:` comments are matched to the preceding `Storage:` comment of the same key.
+fn parse_storage_attrs(attrs: &[Attribute]) -> Result<Vec<StorageAccess>> {
+	lazy_static! {
+		static ref STORAGE: Regex = Regex::new(
+			r#"^\s*Storage:\s*(?P<key>.+?)\s*\(r:\s*(?P<reads>\d+)\s*w:\s*(?P<writes>\d+)\)\s*$"#
+		)
+		.unwrap();
+		static ref PROOF: Regex =
+			Regex::new(r#"^\s*Proof(?: Skipped)?:\s*(?P<key>.+?)\s*\((?P<props>.*)\)\s*$"#)
+				.unwrap();
+		static ref MAX_SIZE: Regex = Regex::new(r#"max_size:\s*Some\((?P<size>\d+)\)"#).unwrap();
+		static ref MODE: Regex = Regex::new(r#"mode:\s*(?P<mode>\w+)"#).unwrap();
+	}
+
+	let mut res: Vec<StorageAccess> = Vec::new();
+	for doc in attrs.iter().filter_map(doc_string) {
+		if let Some(caps) = STORAGE.captures(&doc).expect("Regex is known good") {
+			let (pallet, item) = split_storage_key(&caps["key"]);
+			let parse = |name: &str| {
+				caps[name].parse::<u32>().map_err(|e| {
+					Error::parse(format!("Could not parse {} of storage {}: {:?}", name, item, e))
+				})
+			};
+
+			res.push(StorageAccess {
+				reads: parse("reads")?,
+				writes: parse("writes")?,
+				pallet,
+				item,
+				max_size: None,
+				mode: None,
+			});
+		} else if let Some(caps) = PROOF.captures(&doc).expect("Regex is known good") {
+			let (pallet, item) = split_storage_key(&caps["key"]);
+			let props = caps["props"].replace('`', "");
+			let Some(access) = res.iter_mut().rev().find(|a| a.pallet == pallet && a.item == item)
+			else {
+				log::debug!("Ignoring proof of unaccessed storage {}::{}", pallet, item);
+				continue
+			};
+
+			if let Some(caps) = MAX_SIZE.captures(&props).expect("Regex is known good") {
+				access.max_size = Some(caps["size"].parse().map_err(|e| {
+					Error::parse(format!("Could not parse max_size of {}: {:?}", item, e))
+				})?);
+			}
+			if let Some(caps) = MODE.captures(&props).expect("Regex is known good") {
+				access.mode = Some(caps["mode"].into());
+			}
+		}
+	}
+
+	Ok(res)
+}
+
+fn handle_method(pallet: PalletName, m: &ImplItemFn) -> Result<ChromaticExtrinsic> {
 	let name = m.sig.ident.to_string();
 	// Check the return type to end with `Weight`.
 	if let ReturnType::Type(_, i) = &m.sig.output {
 		if let Type::Path(p) = i.as_ref() {
 			let n = p.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
 			if !n.ends_with("Weight") {
-				return Err(format!("Skipped fn: {} not a weight", name))
+				return Err(Error::at(i.span(), format!("Skipped fn: {} not a weight", name)))
 			}
 		} else {
-			return Err(format!("Skipped fn: {} not a weight", name))
+			return Err(Error::at(i.span(), format!("Skipped fn: {} not a weight", name)))
 		}
 	} else {
-		return Err("Skipped fn: method return type".into())
+		return Err(Error::at(m.sig.span(), "Skipped fn: method return type"))
 	}
 	if m.block.stmts.len() != 1 {
-		return Err("There must be only one statement per weight function".into())
+		return Err(Error::at(
+			m.block.span(),
+			"There must be only one statement per weight function",
+		))
 	}
 	let stmt = m.block.stmts.first().unwrap();
 
@@ -274,21 +631,62 @@ fn handle_method(
 		Stmt::Expr(expr, _) => expr,
 		_ => unreachable!("Expected expression"),
 	};
+	let mut lints = Vec::new();
 	let weight = match parse_expression(expr) {
 		Ok(w) => w,
 		// TODO only do this in V1 compatibility mode.
-		Err(_err) => parse_scalar_expression(expr)?.into_chromatic(crate::Dimension::Time),
+		Err(_err) => {
+			lints.push(Lint::MissingProof);
+			parse_scalar_expression(expr)?.into_chromatic(crate::Dimension::Time)
+		},
 	};
-	// We later on check that the number of weight components matches
-	// the number of components in the term. This cannot be done here
-	// as global constants could mess up the counting.
+	let tokens = expr.to_token_stream();
+	let proof = ["from_parts", "from_proof_size"].iter().any(|i| mentions(tokens.clone(), i));
+	if lints.is_empty() && !proof && mentions(tokens, "from_ref_time") {
+		lints.push(Lint::MissingProof);
+	}
+	if is_zero_call(expr) {
+		lints.push(Lint::ZeroPlaceholder);
+	}
+	// The components are checked against the term by `lint_components`.
 	let comp_ranges = parse_component_attrs(&m.attrs)?;
+	let storage = parse_storage_attrs(&m.attrs)?;
+
+	Ok(ChromaticExtrinsic {
+		name,
+		pallet,
+		term: weight,
+		comp_ranges,
+		storage,
+		summary: Default::default(),
+		metadata: None,
+		kind: WeightKind::Pallet,
+		lints,
+		line: Some(m.sig.fn_token.span.start().line),
+	})
+}
+
+/// Whether the expression is just a `Weight::zero()` call.
+fn is_zero_call(expr: &Expr) -> bool {
+	match expr {
+		Expr::Paren(expr) => is_zero_call(&expr.expr),
+		Expr::Cast(cast) => is_zero_call(&cast.expr),
+		Expr::Call(call) => function_name(call).is_ok_and(|name| name.ends_with("::zero")),
+		_ => false,
+	}
+}
 
-	Ok((name, weight, comp_ranges))
+/// Whether the tokens contain the identifier anywhere.
+fn mentions(tokens: proc_macro2::TokenStream, ident: &str) -> bool {
+	tokens.into_iter().any(|token| match token {
+		proc_macro2::TokenTree::Ident(i) => i == ident,
+		proc_macro2::TokenTree::Group(g) => mentions(g.stream(), ident),
+		_ => false,
+	})
 }
 
 pub(crate) fn parse_expression(expr: &Expr) -> Result<ChromaticTerm> {
-	match expr {
+	let res = match expr {
 		Expr::Paren(expr) => parse_expression(&expr.expr),
 		// TODO check cast
 		Expr::Cast(cast) => parse_expression(&cast.expr),
@@ -299,12 +697,17 @@ pub(crate) fn parse_expression(expr: &Expr) -> Result<ChromaticTerm> {
 			Ok(ChromaticTerm::Var(ident.into()))
 		},
 		Expr::Call(call) => parse_call(call),
-		e => Err(format!("Unexpected expression in pallet expr: {:?}", e.into_token_stream())),
-	}
+		e => Err(Error::parse(format!(
+			"Unexpected expression in pallet expr: {}",
+			e.into_token_stream()
+		))),
+	};
+	// Errors of nested expressions keep their more precise span.
+	res.map_err(|e| e.with_span(expr.span()))
 }
 
 pub(crate) fn parse_scalar_expression(expr: &Expr) -> Result<Term<u128>> {
-	match expr {
+	let res = match expr {
 		Expr::Cast(cast) => parse_scalar_expression(&cast.expr),
 		Expr::Paren(expr) => parse_scalar_expression(&expr.expr),
 		Expr::Lit(lit) => Ok(Term::Scalar(lit_to_value(&lit.lit))),
@@ -314,8 +717,9 @@ pub(crate) fn parse_scalar_expression(expr: &Expr) -> Result<Term<u128>> {
 			Ok(Term::Var(ident.into()))
 		},
 		Expr::Call(call) => parse_scalar_call(call),
-		e => Err(format!("Expected scalar but got: {:?}", e.into_token_stream())),
-	}
+		e => Err(Error::parse(format!("Expected scalar but got: {}", e.into_token_stream()))),
+	};
+	res.map_err(|e| e.with_span(expr.span()))
 }
 
 // Example: T::DbWeight::get()
@@ -324,12 +728,12 @@ fn validate_db_call(call: &Expr) -> Result<()> {
 		Expr::Call(call) => {
 			validate_db_func(&call.func)?;
 			if !call.args.is_empty() {
-				Err("Unexpected arguments".into())
+				Err(Error::parse("Unexpected arguments"))
 			} else {
 				Ok(())
 			}
 		},
-		_ => Err("Unexpected DB call expression".into()),
+		_ => Err(Error::parse("Unexpected DB call expression")),
 	}
 }
 
@@ -348,12 +752,12 @@ fn validate_db_func(func: &Expr) -> Result<()> {
 				!path.ends_with("RocksDbWeight::get") &&
 				!path.ends_with("ParityDbWeight::get")
 			{
-				Err(format!("Unexpected DB path: {}", path))
+				Err(Error::parse(format!("Unexpected DB path: {}", path)))
 			} else {
 				Ok(())
 			}
 		},
-		_ => Err("Unexpected DB func".into()),
+		_ => Err(Error::parse("Unexpected DB func")),
 	}
 }
 
@@ -367,11 +771,11 @@ fn parse_call(call: &ExprCall) -> Result<ChromaticTerm> {
 		parse_parts_args(&call.args)
 	} else if name.ends_with("::zero") {
 		if !call.args.empty_or_trailing() {
-			return Err("Unexpected arguments for `zero`".into())
+			return Err(Error::parse("Unexpected arguments for `zero`"))
 		}
 		Ok(ChromaticTerm::Value(Zero::zero()))
 	} else {
-		Err(format!("Unexpected call: {}", name))
+		Err(Error::UnknownCall { name, file: None, span: Some(call.func.span().into()) })
 	}
 }
 
@@ -384,17 +788,20 @@ fn parse_scalar_call(call: &ExprCall) -> Result<SimpleTerm> {
 		parse_scalar_args(&call.args)
 	} else if name.ends_with("::zero") {
 		if !call.args.empty_or_trailing() {
-			return Err("Unexpected arguments for `zero`".into())
+			return Err(Error::parse("Unexpected arguments for `zero`"))
 		}
 		Ok(SimpleTerm::Value(Zero::zero()))
 	} else {
-		Err(format!("Unexpected call: {}", name))
+		Err(Error::UnknownCall { name, file: None, span: Some(call.func.span().into()) })
 	}
 }
 
 pub(crate) fn parse_parts_args(args: &Punctuated<Expr, Token![,]>) -> Result<ChromaticTerm> {
 	if args.len() != 2 {
-		return Err(format!("Expected two arguments for `from_parts`, got {}", args.len()))
+		return Err(Error::parse(format!(
+			"Expected two arguments for `from_parts`, got {}",
+			args.len()
+		)))
 	}
 
 	let t = parse_scalar_expression(&args[0])?.into_chromatic(Dimension::Time);
@@ -442,7 +849,7 @@ pub(crate) fn parse_method_call(call: &ExprMethodCall) -> Result<ChromaticTerm>
 		//"ref_time" => {
 		//	// SWC is still only using 1D weights, so just do nothing…
 		//	if !call.args.empty_or_trailing() {
-		//		return Err("Unexpected arguments on `ref_time`".into())
+		//		return Err(Error::parse("Unexpected arguments on `ref_time`"))
 		//	}
 		//	parse_expression(&call.receiver)
 		//},
@@ -467,7 +874,11 @@ pub(crate) fn parse_method_call(call: &ExprMethodCall) -> Result<ChromaticTerm>
 			parse_args(&call.args)?.into(),
 		)),
 		"into" => parse_expression(&call.receiver),
-		_ => Err(format!("Unknown function: {}", name)),
+		_ => Err(Error::UnknownCall {
+			name: name.into(),
+			file: None,
+			span: Some(call.method.span().into()),
+		}),
 	}
 }
 
@@ -477,7 +888,7 @@ pub(crate) fn parse_scalar_method_call(call: &ExprMethodCall) -> Result<Term<u12
 	match name {
 		"ref_time" => {
 			if !call.args.empty_or_trailing() {
-				return Err("Unexpected arguments on `ref_time`".into())
+				return Err(Error::parse("Unexpected arguments on `ref_time`"))
 			}
 			parse_scalar_expression(&call.receiver)
 		},
@@ -502,15 +913,19 @@ pub(crate) fn parse_scalar_method_call(call: &ExprMethodCall) -> Result<Term<u12
 			parse_scalar_args(&call.args)?.into(),
 		)),
 		"into" => parse_scalar_expression(&call.receiver),
-		_ => Err(format!("Unknown function: {}", name)),
+		_ => Err(Error::UnknownCall {
+			name: name.into(),
+			file: None,
+			span: Some(call.method.span().into()),
+		}),
 	}
 }
 
 fn extract_arg(args: &Punctuated<Expr, Token![,]>) -> Result<&Expr> {
 	if args.len() != 1 {
-		return Err(format!("Expected one argument, got {}", args.len()))
+		return Err(Error::parse(format!("Expected one argument, got {}", args.len())))
 	}
-	args.first().ok_or_else(|| "Empty args".into())
+	args.first().ok_or_else(|| Error::parse("Empty args"))
 }
 
 fn parse_args(args: &Punctuated<Expr, Token![,]>) -> Result<ChromaticTerm> {
@@ -533,6 +948,6 @@ pub(crate) fn lit_to_value(lit: &Lit) -> u128 {
 fn function_name(call: &ExprCall) -> Result<String> {
 	match call.func.as_ref() {
 		Expr::Path(p) => Ok(path_to_string(&p.path, Some("::"))),
-		_ => Err("Unexpected function".into()),
+		_ => Err(Error::parse("Unexpected function")),
 	}
 }
@@ -1,7 +1,7 @@
 use std::path::Path;
-use syn::{BinOp, Expr, ExprStruct, Item, ItemConst, Type};
+use syn::{spanned::Spanned, BinOp, Expr, ExprStruct, Item, ItemConst, Type};
 
-use crate::{parse::path_to_string, term::SimpleTerm as Term};
+use crate::{parse::path_to_string, term::SimpleTerm as Term, Error};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Db {
@@ -56,22 +56,23 @@ macro_rules! cwrites {
 /// Parses a storage weight file.
 ///
 /// These files are often named: `paritydb_weights.rs.txt` or `rocksdb_weights.rs.txt`.
-pub fn parse_file(file: &Path) -> Result<Weights, String> {
+pub fn parse_file(file: &Path) -> Result<Weights, Error> {
 	let content = super::read_file(file)?;
-	parse_content(content)
+	parse_content(content).map_err(|e| e.in_file(file))
 }
 
-pub fn parse_content(content: String) -> Result<Weights, String> {
-	let ast = syn::parse_file(&content).map_err(|e| e.to_string())?;
+pub fn parse_content(content: String) -> Result<Weights, Error> {
+	let ast = syn::parse_file(&content)
+		.map_err(|e| Error::at(e.span(), format!("syn refused to parse content: {}", e)))?;
 	for item in ast.items {
 		if let Ok(res) = handle_item(&item) {
 			return Ok(res)
 		}
 	}
-	Err("No DB weights found".to_string())
+	Err(Error::parse("No DB weights found"))
 }
 
-fn handle_item(item: &Item) -> Result<Weights, String> {
+fn handle_item(item: &Item) -> Result<Weights, Error> {
 	match item {
 		// The current Substrate template has a useless `constants` mod.
 		Item::Mod(m) => {
@@ -84,20 +85,20 @@ fn handle_item(item: &Item) -> Result<Weights, String> {
 							return res
 						}
 					}
-					return Err("Did not find parameter_types!".into())
+					return Err(Error::at(m.ident.span(), "Did not find parameter_types!"))
 				}
 			}
-			Err(format!("Unexpected module: {}", m.ident))
+			Err(Error::at(m.ident.span(), format!("Unexpected module: {}", m.ident)))
 		},
 		Item::Macro(m) => {
 			let name = m.mac.path.segments.last();
 			if name.unwrap().ident == "parameter_types" {
 				parse_macro(m.mac.tokens.clone())
 			} else {
-				Err("Unexpected macro def".into())
+				Err(Error::at(m.mac.path.span(), "Unexpected macro def"))
 			}
 		},
-		_ => Err("Could not find DB weights in the file".into()),
+		_ => Err(Error::at(item.span(), "Could not find DB weights in the file")),
 	}
 }
 
@@ -110,45 +111,45 @@ fn handle_item(item: &Item) -> Result<Weights, String> {
 ///     write: 100_000 * constants::WEIGHT_PER_NANOS,
 /// };
 /// ```
-fn parse_macro(tokens: proc_macro2::TokenStream) -> Result<Weights, String> {
-	let def: ItemConst = syn::parse2(tokens).map_err(|e| e.to_string())?;
+fn parse_macro(tokens: proc_macro2::TokenStream) -> Result<Weights, Error> {
+	let def: ItemConst = syn::parse2(tokens).map_err(|e| Error::at(e.span(), e.to_string()))?;
 	let name = def.ident.to_string();
 
 	let db = match name.as_str() {
 		"RocksDbWeight" => Db::Rocks,
 		"ParityDbWeight" => Db::Parity,
-		_ => return Err(format!("Unexpected const name: {}", name)),
+		_ => return Err(Error::at(def.ident.span(), format!("Unexpected const name: {}", name))),
 	};
 	let type_name = type_to_string(&def.ty, None)?;
 	if type_name != "RuntimeDbWeight" {
-		return Err(format!("Unexpected const type: {}", type_name))
+		return Err(Error::at(def.ty.span(), format!("Unexpected const type: {}", type_name)))
 	}
 	match def.expr.as_ref() {
 		Expr::Struct(s) => {
 			let weights = parse_runtime_db_weight(s)?;
 			Ok(Weights { db, weights })
 		},
-		_ => Err("Unexpected const value".into()),
+		e => Err(Error::at(e.span(), "Unexpected const value")),
 	}
 }
 
-fn parse_runtime_db_weight(expr: &ExprStruct) -> Result<RWs, String> {
+fn parse_runtime_db_weight(expr: &ExprStruct) -> Result<RWs, Error> {
 	let name = path_to_string(&expr.path, None);
 	if name != "RuntimeDbWeight" {
-		return Err(format!("Unexpected struct name: {}", name))
+		return Err(Error::at(expr.path.span(), format!("Unexpected struct name: {}", name)))
 	} else if expr.fields.len() != 2 {
-		return Err("Unexpected struct fields".into())
+		return Err(Error::at(expr.fields.span(), "Unexpected struct fields"))
 	}
 	let reads = expr
 		.fields
 		.iter()
 		.find(|f| member_to_string(&f.member) == "read")
-		.ok_or("No read field found")?;
+		.ok_or_else(|| Error::at(expr.span(), "No read field found"))?;
 	let writes = expr
 		.fields
 		.iter()
 		.find(|f| member_to_string(&f.member) == "write")
-		.ok_or("No write field found")?;
+		.ok_or_else(|| Error::at(expr.span(), "No write field found"))?;
 
 	let read = parse_expression(&reads.expr)?;
 	let write = parse_expression(&writes.expr)?;
@@ -156,7 +157,7 @@ fn parse_runtime_db_weight(expr: &ExprStruct) -> Result<RWs, String> {
 	Ok(RWs { read, write })
 }
 
-fn parse_expression(expr: &Expr) -> Result<Term, String> {
+fn parse_expression(expr: &Expr) -> Result<Term, Error> {
 	match expr {
 		Expr::Binary(bin) => {
 			let left = parse_expression(&bin.left)?.into();
@@ -165,22 +166,22 @@ fn parse_expression(expr: &Expr) -> Result<Term, String> {
 			let term = match bin.op {
 				BinOp::Mul(_) => Term::Mul(left, right),
 				BinOp::Add(_) => Term::Add(left, right),
-				_ => return Err("Unexpected operator".into()),
+				_ => return Err(Error::at(bin.op.span(), "Unexpected operator")),
 			};
 			Ok(term)
 		},
 		Expr::Lit(lit) => Ok(Term::Scalar(super::pallet::lit_to_value(&lit.lit))),
 		Expr::Path(p) => Ok(Term::Var(crate::term::VarValue(path_to_string(&p.path, Some("::"))))),
-		_ => Err("Unexpected expression storage expr".into()),
+		e => Err(Error::at(e.span(), "Unexpected expression storage expr")),
 	}
 }
 
 /// Expects a path to a type and returns the type name.
-fn type_to_string(p: &syn::Type, delimiter: Option<&str>) -> Result<String, String> {
+fn type_to_string(p: &syn::Type, delimiter: Option<&str>) -> Result<String, Error> {
 	if let Type::Path(p) = p {
 		Ok(path_to_string(&p.path, delimiter))
 	} else {
-		Err("Unexpected type".into())
+		Err(Error::at(p.span(), "Unexpected type"))
 	}
 }
 
@@ -0,0 +1,208 @@
+//! Pairs removed and added extrinsics that were renamed or moved to another pallet.
+//!
+//! Without this, a renamed extrinsic shows up as one `Removed` and one `Added` entry and its weight
+//! change is hidden. The pairs are instead reported as [`RelativeChange::Renamed`].
+//!
+//! [`RelativeChange::Renamed`]: crate::RelativeChange::Renamed
+
+use clap::Args;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+	parse::pallet::{ChromaticExtrinsic, StorageAccess},
+	term::{Monomial, SimpleTerm},
+	Dimension, ExtrinsicName, PalletName,
+};
+
+/// Parameters for detecting renamed and moved extrinsics.
+#[derive(Debug, Clone, PartialEq, Args)]
+pub struct RenameParams {
+	/// Report removed and added extrinsics that look alike as renamed.
+	///
+	/// An extrinsic can either be renamed within its pallet or moved to another pallet under the
+	/// same name.
+	#[clap(long)]
+	pub detect_renames: bool,
+
+	/// Minimal similarity in `(0, 1]` that a removed and added extrinsic need to be paired.
+	#[clap(long, value_name = "SIMILARITY", default_value = "0.75", value_parser = crate::parse_ratio)]
+	pub rename_threshold: f64,
+}
+
+impl Default for RenameParams {
+	fn default() -> Self {
+		Self { detect_renames: false, rename_threshold: 0.75 }
+	}
+}
+
+/// A removed extrinsic that was paired with an added one.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct Rename {
+	/// Pallet of the removed extrinsic.
+	pub old_file: PalletName,
+	/// Name of the removed extrinsic.
+	pub old_name: ExtrinsicName,
+	/// Pallet of the added extrinsic.
+	#[serde(skip)]
+	pub new_file: PalletName,
+	/// Name of the added extrinsic.
+	#[serde(skip)]
+	pub new_name: ExtrinsicName,
+	/// How similar both are; between 0 and 1. See [`similarity`].
+	pub similarity: f64,
+}
+
+impl Rename {
+	/// Whether the extrinsic was moved to another pallet instead of being renamed.
+	pub fn is_move(&self) -> bool {
+		self.old_file != self.new_file
+	}
+}
+
+/// Pairs the extrinsics that only exist in `olds` with the ones that only exist in `news`.
+///
+/// Only extrinsics of the same pallet or with the same name are considered. Each extrinsic is
+/// paired at most once; the most similar pairs win. Returns nothing if the detection is disabled.
+pub fn detect_renames(
+	olds: &[ChromaticExtrinsic],
+	news: &[ChromaticExtrinsic],
+	params: &RenameParams,
+) -> Vec<Rename> {
+	if !params.detect_renames {
+		return Vec::new()
+	}
+	let key = |e: &ChromaticExtrinsic| (e.pallet.clone(), e.name.clone());
+	let old_keys = olds.iter().map(key).collect::<BTreeSet<_>>();
+	let new_keys = news.iter().map(key).collect::<BTreeSet<_>>();
+	let removed = olds.iter().filter(|o| !new_keys.contains(&key(o))).collect::<Vec<_>>();
+	let added = news.iter().filter(|n| !old_keys.contains(&key(n))).collect::<Vec<_>>();
+
+	let mut candidates = Vec::new();
+	for old in removed.iter() {
+		for new in added.iter() {
+			if old.pallet != new.pallet && old.name != new.name {
+				continue
+			}
+			let similarity = similarity(old, new);
+			if similarity >= params.rename_threshold {
+				candidates.push((similarity, *old, *new));
+			}
+		}
+	}
+	// Most similar first; ties are broken by name to stay deterministic.
+	candidates.sort_by(|(sa, oa, na), (sb, ob, nb)| {
+		sb.total_cmp(sa)
+			.then_with(|| key(oa).cmp(&key(ob)))
+			.then_with(|| key(na).cmp(&key(nb)))
+	});
+
+	let (mut paired_old, mut paired_new) = (BTreeSet::new(), BTreeSet::new());
+	let mut renames = Vec::new();
+	for (similarity, old, new) in candidates {
+		if paired_old.contains(&key(old)) || paired_new.contains(&key(new)) {
+			continue
+		}
+		paired_old.insert(key(old));
+		paired_new.insert(key(new));
+		renames.push(Rename {
+			old_file: old.pallet.clone(),
+			old_name: old.name.clone(),
+			new_file: new.pallet.clone(),
+			new_name: new.name.clone(),
+			similarity,
+		});
+	}
+
+	renames
+}
+
+/// How similar two extrinsics are; between 0 and 1.
+///
+/// Averages the similarity of the weight terms, the storage annotations (if any) and the names.
+/// The terms count twice since they are the most telling.
+pub fn similarity(old: &ChromaticExtrinsic, new: &ChromaticExtrinsic) -> f64 {
+	let mut parts = vec![(2.0, term_similarity(old, new))];
+	if !old.storage.is_empty() || !new.storage.is_empty() {
+		parts.push((1.0, storage_similarity(&old.storage, &new.storage)));
+	}
+	let name = if old.name == new.name {
+		name_similarity(&old.pallet, &new.pallet)
+	} else {
+		name_similarity(&old.name, &new.name)
+	};
+	parts.push((1.0, name));
+
+	let weights = parts.iter().map(|(w, _)| w).sum::<f64>();
+	parts.iter().map(|(w, s)| w * s).sum::<f64>() / weights
+}
+
+/// Compares the coefficients of both dimensions monomial by monomial.
+fn term_similarity(old: &ChromaticExtrinsic, new: &ChromaticExtrinsic) -> f64 {
+	if old.term == new.term {
+		return 1.0
+	}
+	let coefficients = |ext: &ChromaticExtrinsic, unit| {
+		ext.term.simplify(unit).ok().as_ref().and_then(SimpleTerm::coefficients)
+	};
+	let mut ratios = Vec::new();
+	for unit in Dimension::all() {
+		let (Some(o), Some(n)) = (coefficients(old, unit), coefficients(new, unit)) else {
+			return 0.0
+		};
+		let monomials = o.keys().chain(n.keys()).collect::<BTreeSet<&Monomial>>();
+		for monomial in monomials {
+			let (a, b) = (o.get(monomial).copied(), n.get(monomial).copied());
+			ratios.push(match (a.unwrap_or_default(), b.unwrap_or_default()) {
+				(a, b) if a == b => 1.0,
+				(a, b) => a.min(b) as f64 / a.max(b) as f64,
+			});
+		}
+	}
+	if ratios.is_empty() {
+		return 1.0
+	}
+	ratios.iter().sum::<f64>() / ratios.len() as f64
+}
+
+/// Jaccard index of the accessed storage items and how often they are accessed.
+fn storage_similarity(old: &[StorageAccess], new: &[StorageAccess]) -> f64 {
+	let by_key = |accesses: &[StorageAccess]| {
+		let mut res = BTreeMap::<_, (u32, u32)>::new();
+		for a in accesses {
+			let e = res.entry((a.pallet.clone(), a.item.clone())).or_default();
+			e.0 = e.0.saturating_add(a.reads);
+			e.1 = e.1.saturating_add(a.writes);
+		}
+		res
+	};
+	let (old, new) = (by_key(old), by_key(new));
+	let keys = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+	if keys.is_empty() {
+		return 1.0
+	}
+	let same = keys.iter().filter(|k| old.get(k) == new.get(k)).count();
+	same as f64 / keys.len() as f64
+}
+
+/// Similarity of two names by their longest common subsequence.
+///
+/// Case and `_` separators are ignored, so `set_payee` and `setPayee` are the same.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+	let norm =
+		|s: &str| s.chars().filter(|c| *c != '_').flat_map(char::to_lowercase).collect::<Vec<_>>();
+	let (a, b) = (norm(a), norm(b));
+	if a.is_empty() && b.is_empty() {
+		return 1.0
+	}
+
+	let mut row = vec![0usize; b.len() + 1];
+	for ca in a.iter() {
+		let mut diag = 0;
+		for (j, cb) in b.iter().enumerate() {
+			let up = row[j + 1];
+			row[j + 1] = if ca == cb { diag + 1 } else { up.max(row[j]) };
+			diag = up;
+		}
+	}
+	2.0 * row[b.len()] as f64 / (a.len() + b.len()) as f64
+}
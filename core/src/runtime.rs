@@ -0,0 +1,162 @@
+//! Compares the same pallets across several runtimes at once.
+//!
+//! Repos with multiple runtimes have one weight file per pallet and runtime, for example
+//! `runtime/{a,b}/src/weights/pallet_balances.rs`. The runtime of a file is captured from its path
+//! and the files of each runtime are compared on their own. The result is a matrix of extrinsic ×
+//! runtime that shows whether a change hits all runtimes or only some.
+
+use clap::Args;
+use fancy_regex::Regex;
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::Path,
+};
+
+use crate::{
+	compare_files, filter_changes, parse::pallet::ChromaticExtrinsic, CompareParams, Error,
+	ExtrinsicDiff, ExtrinsicName, FilterParams, TotalDiff,
+};
+
+/// Parameters for grouping the weight files by runtime.
+#[derive(Debug, Clone, PartialEq, Args, Default)]
+pub struct RuntimeParams {
+	/// Compare per runtime and print a matrix of extrinsic × runtime.
+	///
+	/// The runtime of a file is captured from its path by this regex: either the group named
+	/// `runtime`, the first group or the whole match. Example: `runtime/([^/]+)/`. Files that do
+	/// not match are skipped.
+	#[clap(long, value_name = "REGEX")]
+	pub runtime_pattern: Option<String>,
+}
+
+/// The changes of each extrinsic per runtime. See [`compare_runtimes`].
+#[derive(Clone, serde::Serialize)]
+#[cfg_attr(feature = "bloat", derive(Debug))]
+pub struct RuntimeMatrix {
+	/// All runtimes in alphabetical order.
+	pub runtimes: Vec<String>,
+	pub rows: Vec<RuntimeRow>,
+}
+
+/// One extrinsic of a pallet in all runtimes.
+#[derive(Clone, serde::Serialize)]
+#[cfg_attr(feature = "bloat", derive(Debug))]
+pub struct RuntimeRow {
+	/// File name of the pallet; the same in all runtimes.
+	pub file: String,
+	pub name: ExtrinsicName,
+	/// The change per runtime. Runtimes without the pallet or extrinsic are missing.
+	pub changes: BTreeMap<String, ExtrinsicDiff>,
+}
+
+/// Compares the old and new extrinsics of each runtime on their own.
+///
+/// The runtime is captured from the pallet path of the extrinsics, so they must be parsed with
+/// their path and not only their file name. Within a runtime the pallets are then identified by
+/// their file name.
+pub fn compare_runtimes(
+	olds: Vec<ChromaticExtrinsic>,
+	news: Vec<ChromaticExtrinsic>,
+	params: &CompareParams,
+	filter: &FilterParams,
+	pattern: &str,
+) -> Result<RuntimeMatrix, Error> {
+	let regex = Regex::new(pattern)
+		.map_err(|e| Error::InvalidParam { msg: format!("Invalid runtime pattern: {}", e) })?;
+	let mut olds = by_runtime(olds, &regex);
+	let mut news = by_runtime(news, &regex);
+	let runtimes = olds
+		.keys()
+		.chain(news.keys())
+		.cloned()
+		.collect::<BTreeSet<String>>()
+		.into_iter()
+		.collect::<Vec<_>>();
+
+	let mut rows = BTreeMap::<(String, ExtrinsicName), BTreeMap<String, ExtrinsicDiff>>::new();
+	for runtime in &runtimes {
+		let olds = olds.remove(runtime).unwrap_or_default();
+		let news = news.remove(runtime).unwrap_or_default();
+		log::info!("Comparing runtime {}", runtime);
+
+		for diff in compare_files(olds, news, params, filter)? {
+			rows.entry((diff.file.clone(), diff.name.clone()))
+				.or_default()
+				.insert(runtime.clone(), diff);
+		}
+	}
+
+	let rows = rows
+		.into_iter()
+		.map(|((file, name), changes)| RuntimeRow { file, name, changes })
+		.collect();
+	Ok(RuntimeMatrix { runtimes, rows })
+}
+
+/// Groups the extrinsics by runtime and strips their pallet down to the file name.
+fn by_runtime(
+	exts: Vec<ChromaticExtrinsic>,
+	regex: &Regex,
+) -> BTreeMap<String, Vec<ChromaticExtrinsic>> {
+	let mut res = BTreeMap::<_, Vec<_>>::new();
+	for mut ext in exts {
+		let captures = regex.captures(&ext.pallet).ok().flatten();
+		let Some(runtime) = captures
+			.as_ref()
+			.and_then(|c| c.name("runtime").or_else(|| c.get(1)).or_else(|| c.get(0)))
+		else {
+			log::warn!("Skipping {}::{}: no runtime in its path", ext.pallet, ext.name);
+			continue
+		};
+		let runtime = runtime.as_str().to_string();
+		let path = Path::new(&ext.pallet);
+		ext.pallet = path
+			.file_name()
+			.map_or_else(|| ext.pallet.clone(), |f| f.to_string_lossy().into_owned());
+		res.entry(runtime).or_default().push(ext);
+	}
+	res
+}
+
+impl RuntimeMatrix {
+	/// Keeps the rows where any runtime passes the filter; their other runtimes are kept as well.
+	///
+	/// This shows whether a change that is relevant in one runtime also happened in the others.
+	pub fn filter(self, filter: &FilterParams) -> Self {
+		let rows = self
+			.rows
+			.into_iter()
+			.filter(|row| {
+				!filter_changes(row.changes.values().cloned().collect(), filter).is_empty()
+			})
+			.collect();
+		Self { rows, ..self }
+	}
+
+	/// Sorts the rows by the largest change of any runtime; ascending like [`crate::sort_changes`].
+	pub fn sort(&mut self) {
+		fn largest(row: &RuntimeRow) -> Option<&ExtrinsicDiff> {
+			row.changes.values().max_by(|a, b| a.change.cmp(&b.change))
+		}
+		self.rows.sort_by(|a, b| match (largest(a), largest(b)) {
+			(Some(a), Some(b)) => a.change.cmp(&b.change),
+			(a, b) => a.is_some().cmp(&b.is_some()),
+		});
+	}
+
+	/// All changes of all runtimes with the runtime prefixed to their file.
+	pub fn diffs(&self) -> TotalDiff {
+		self.rows
+			.iter()
+			.flat_map(|row| row.changes.iter())
+			.map(|(runtime, diff)| ExtrinsicDiff {
+				file: format!("{}/{}", runtime, diff.file),
+				..diff.clone()
+			})
+			.collect()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.rows.is_empty()
+	}
+}
@@ -11,7 +11,7 @@ use std::collections::BTreeMap as Map;
 pub const STORAGE_READ_VAR: &str = "READ";
 pub const STORAGE_WRITE_VAR: &str = "WRITE";
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "bloat", derive(Default))]
 pub struct Scope<T> {
 	vars: Map<String, T>,
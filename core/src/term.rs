@@ -3,16 +3,21 @@
 use crate::traits::{One, Zero};
 use lazy_static::__Deref;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet as Set, fmt};
+use std::{
+	collections::{btree_map::Entry, BTreeMap as Map, BTreeSet as Set},
+	fmt,
+};
 use syn::{BinOp, ExprBinary};
 
-use crate::{scope::Scope, traits::*};
+use crate::{scope::Scope, traits::*, Error};
 
 /// A symbolic term that can be used to express simple arithmetic.
 ///
 /// Can only be evaluated to a concrete value within a [`crate::scope::Scope`].
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq)]
-pub enum Term<T> {
+/// Two terms are equal if their [`Term::normalize`]d forms are equal.
+/// They are not ordered, since a structural order would disagree with this equality.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Term<T: Coefficient> {
 	Value(T),
 	Scalar(u128),
 	Var(VarValue),
@@ -133,21 +138,52 @@ macro_rules! cmul {
 
 impl SimpleTerm {
 	/// Evaluates the term within the given scope to a concrete value.
-	pub fn eval(&self, ctx: &crate::scope::SimpleScope) -> Result<u128, String> {
+	///
+	/// Uses the saturating `u64` arithmetic of the runtime.
+	pub fn eval(&self, ctx: &crate::scope::SimpleScope) -> Result<u128, Error> {
+		self.eval_saturating(ctx).map(|(value, _)| value)
+	}
+
+	/// Evaluates the term and reports whether any addition or multiplication saturated.
+	pub fn eval_saturating(&self, ctx: &crate::scope::SimpleScope) -> Result<(u128, bool), Error> {
+		let exceeds = |exact: Option<u128>| exact.map_or(true, |v| v > MAX_WEIGHT);
 		match self {
-			Self::Value(x) => Ok(*x),
-			Self::Scalar(x) => Ok(*x),
-			Self::Add(x, y) => Ok(x.eval(ctx)? + y.eval(ctx)?),
-			Self::Mul(x, y) => Ok(x.eval(ctx)? * y.eval(ctx)?),
+			Self::Value(x) | Self::Scalar(x) => Ok(((*x).min(MAX_WEIGHT), *x > MAX_WEIGHT)),
+			Self::Add(x, y) => {
+				let ((a, sa), (b, sb)) = (x.eval_saturating(ctx)?, y.eval_saturating(ctx)?);
+				Ok((saturating_add(a, b), sa || sb || exceeds(a.checked_add(b))))
+			},
+			Self::Mul(x, y) => {
+				let ((a, sa), (b, sb)) = (x.eval_saturating(ctx)?, y.eval_saturating(ctx)?);
+				Ok((saturating_mul(a, b), sa || sb || exceeds(a.checked_mul(b))))
+			},
 			Self::Var(x) =>
 				if let Some(var) = ctx.get(x) {
-					var.eval(ctx)
+					var.eval_saturating(ctx)
 				} else {
-					Err(format!("Variable '{}' not found", x.deref()))
+					Err(Error::Eval { msg: format!("Variable '{}' not found", x.deref()) })
 				},
 		}
 	}
 
+	/// The non-zero coefficient of each monomial of the [`Self::normalize`]d term.
+	///
+	/// Returns `None` if the term cannot be normalized.
+	pub fn coefficients(&self) -> Option<Map<Monomial, u128>> {
+		let polynomial = self.polynomial()?;
+		let coefficients = polynomial
+			.into_iter()
+			.filter(|(_, (_, coeff))| !coeff.is_zero())
+			.map(|(key, (vars, coeff))| {
+				let coeff = match coeff {
+					Coeff::Scalar(c) | Coeff::Value(c) => c,
+				};
+				(Monomial { key, vars: vars.into_iter().map(Into::into).collect() }, coeff)
+			})
+			.collect();
+		Some(coefficients)
+	}
+
 	pub fn into_chromatic(self, unit: crate::Dimension) -> ChromaticTerm {
 		match self {
 			Self::Value(x) | Self::Scalar(x) =>
@@ -172,9 +208,83 @@ impl SimpleTerm {
 	}
 }
 
+impl<T: Coefficient> Term<T> {
+	/// Converts the term into a canonical polynomial.
+	///
+	/// The result is a sum of monomials, each one a coefficient times its sorted variables.
+	/// Monomials with the same variables are merged and those with a zero coefficient dropped.
+	/// The constant comes first, then the monomials sorted by their degree and variables.
+	/// Component names come before upper case constants like `READ` and `WRITE`.
+	///
+	/// Returns the term unchanged if it cannot be normalized, for example because a coefficient
+	/// overflows.
+	pub fn normalize(&self) -> Self {
+		self.polynomial().map(Self::from_polynomial).unwrap_or_else(|| self.clone())
+	}
+
+	fn polynomial(&self) -> Option<Polynomial<T>> {
+		let monomial =
+			|vars: Vec<VarValue>, coeff| Map::from([(monomial_key(&vars), (vars, coeff))]);
+		match self {
+			Self::Value(v) => Some(monomial(vec![], Coeff::Value(v.clone()))),
+			Self::Scalar(s) => Some(monomial(vec![], Coeff::Scalar(*s))),
+			Self::Var(v) => Some(monomial(vec![v.clone()], Coeff::Scalar(1))),
+			Self::Add(l, r) => {
+				let mut sum = l.polynomial()?;
+				for (key, (vars, coeff)) in r.polynomial()? {
+					add_monomial(&mut sum, key, vars, coeff)?;
+				}
+				Some(sum)
+			},
+			Self::Mul(l, r) => {
+				let (l, r) = (l.polynomial()?, r.polynomial()?);
+				let mut product = Polynomial::new();
+				for (l_vars, l_coeff) in l.values() {
+					for (r_vars, r_coeff) in r.values() {
+						let mut vars = l_vars.iter().chain(r_vars).cloned().collect::<Vec<_>>();
+						vars.sort_by_key(var_key);
+						add_monomial(
+							&mut product,
+							monomial_key(&vars),
+							vars,
+							l_coeff.mul(r_coeff)?,
+						)?;
+					}
+				}
+				Some(product)
+			},
+		}
+	}
+
+	fn from_polynomial(polynomial: Polynomial<T>) -> Self {
+		polynomial
+			.into_values()
+			.filter(|(_, coeff)| !coeff.is_zero())
+			.map(|(vars, coeff)| {
+				vars.into_iter().fold(coeff.into_term(), |acc, var| {
+					Self::Mul(acc.into(), Self::Var(var).into())
+				})
+			})
+			.reduce(|acc, monomial| Self::Add(acc.into(), monomial.into()))
+			.unwrap_or(Self::Value(T::zero()))
+	}
+
+	/// Compares the structure of two terms without normalizing them.
+	pub(crate) fn same_structure(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Value(a), Self::Value(b)) => a == b,
+			(Self::Scalar(a), Self::Scalar(b)) => a == b,
+			(Self::Var(a), Self::Var(b)) => a == b,
+			(Self::Add(a, b), Self::Add(c, d)) | (Self::Mul(a, b), Self::Mul(c, d)) =>
+				a.same_structure(c) && b.same_structure(d),
+			_ => false,
+		}
+	}
+}
+
 impl<T> Term<T>
 where
-	T: Clone + core::fmt::Display + One + Zero + PartialEq + Eq + ValueFormatter,
+	T: Coefficient + core::fmt::Display + One + Eq + ValueFormatter,
 {
 	pub fn is_const_zero(&self) -> bool {
 		match self {
@@ -309,9 +419,9 @@ where
 		}
 	}
 
-	pub fn visit<F, R>(&self, f: &mut F) -> Result<Vec<R>, String>
+	pub fn visit<F, R>(&self, f: &mut F) -> Result<Vec<R>, Error>
 	where
-		F: FnMut(&Self) -> Result<R, String>,
+		F: FnMut(&Self) -> Result<R, Error>,
 	{
 		let mut res = Vec::<R>::new();
 		res.push(f(self)?);
@@ -364,33 +474,29 @@ where
 
 impl ChromaticTerm {
 	/// Evaluates the term within the given scope to a concrete value.
-	pub fn eval(&self, ctx: &crate::scope::ChromaticScope) -> Result<Weight, String> {
+	///
+	/// Uses the saturating `u64` arithmetic of the runtime.
+	pub fn eval(&self, ctx: &crate::scope::ChromaticScope) -> Result<Weight, Error> {
 		match self {
-			Self::Value(x) => Ok(x.clone()),
+			Self::Value(x) => Ok(Weight::zero().saturating_add(x)),
 			Self::Scalar(_) => unreachable!("Scalars cannot be evaluated; qed"),
-			Self::Add(x, y) => {
-				let (a, b) = x.eval(ctx)?.into();
-				let (m, n) = y.eval(ctx)?.into();
-				Ok((a + m, b + n).into())
-			},
+			Self::Add(x, y) => Ok(x.eval(ctx)?.saturating_add(&y.eval(ctx)?)),
 			Self::Mul(x, y) => match (x.as_ref(), y.as_ref()) {
-				(Self::Scalar(x), y) => {
-					let (a, b) = y.eval(ctx)?.into();
-					Ok((*x * a, *x * b).into())
-				},
-				(x, Self::Scalar(y)) => {
-					let (a, b) = x.eval(ctx)?.into();
-					Ok((*y * a, *y * b).into())
-				},
+				(Self::Scalar(x), y) => Ok(y.eval(ctx)?.mul_scalar(*x)),
+				(x, Self::Scalar(y)) => Ok(x.eval(ctx)?.mul_scalar(*y)),
 				(Self::Var(x), y) => match ctx.get(x) {
 					Some(Self::Scalar(x)) => Ok(y.eval(ctx)?.mul_scalar(x)),
-					Some(_) => Err(format!("Variable '{}' is not a scalar", x.deref())),
-					None => Err(format!("Variable '{}' not found", x.deref())),
+					Some(_) => Err(Error::Eval {
+						msg: format!("Variable '{}' is not a scalar", x.deref()),
+					}),
+					None => Err(Error::Eval { msg: format!("Variable '{}' not found", x.deref()) }),
 				},
 				(x, Self::Var(y)) => match ctx.get(y) {
 					Some(Self::Scalar(y)) => Ok(x.eval(ctx)?.mul_scalar(y)),
-					Some(_) => Err(format!("Variable '{}' is not a scalar", y.deref())),
-					None => Err(format!("Variable '{}' not found", y.deref())),
+					Some(_) => Err(Error::Eval {
+						msg: format!("Variable '{}' is not a scalar", y.deref()),
+					}),
+					None => Err(Error::Eval { msg: format!("Variable '{}' not found", y.deref()) }),
 				},
 				_ => unreachable!("Cannot multiply two terms; qed"),
 			},
@@ -398,12 +504,12 @@ impl ChromaticTerm {
 				if let Some(var) = ctx.get(x) {
 					var.eval(ctx)
 				} else {
-					Err(format!("Variable '{}' not found", x.deref()))
+					Err(Error::Eval { msg: format!("Variable '{}' not found", x.deref()) })
 				},
 		}
 	}
 
-	pub fn simplify(&self, unit: crate::Dimension) -> Result<SimpleTerm, String> {
+	pub fn simplify(&self, unit: crate::Dimension) -> Result<SimpleTerm, Error> {
 		self.for_values(|t| match t {
 			Self::Value(Weight { time, .. }) if unit == crate::Dimension::Time =>
 				Ok(SimpleTerm::Value(*time)),
@@ -415,9 +521,9 @@ impl ChromaticTerm {
 		})
 	}
 
-	pub fn for_values<F>(&self, f: F) -> Result<SimpleTerm, String>
+	pub fn for_values<F>(&self, f: F) -> Result<SimpleTerm, Error>
 	where
-		F: Fn(&Self) -> Result<SimpleTerm, String> + Clone,
+		F: Fn(&Self) -> Result<SimpleTerm, Error> + Clone,
 	{
 		match self {
 			v @ Self::Value(_) | v @ Self::Scalar(_) | v @ Self::Var(_) => f(v),
@@ -454,15 +560,176 @@ impl ChromaticTerm {
 
 impl<T> fmt::Display for Term<T>
 where
-	T: Clone + core::fmt::Display + One + Zero + PartialEq + Eq + ValueFormatter,
+	T: Coefficient + core::fmt::Display + One + Eq + ValueFormatter,
 {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		write!(f, "{}", self.fmt_with_bracket(true))
+		write!(f, "{}", self.normalize().fmt_with_bracket(true))
+	}
+}
+
+impl<T: Coefficient> PartialEq for Term<T> {
+	fn eq(&self, other: &Self) -> bool {
+		match (self.polynomial(), other.polynomial()) {
+			(Some(a), Some(b)) => {
+				let non_zero = |p: Polynomial<T>| {
+					p.into_iter().filter(|(_, (_, c))| !c.is_zero()).collect::<Vec<_>>()
+				};
+				let (a, b) = (non_zero(a), non_zero(b));
+				a.len() == b.len() &&
+					a.iter().zip(&b).all(|((ka, (_, ca)), (kb, (_, cb)))| ka == kb && ca == cb)
+			},
+			_ => self.same_structure(other),
+		}
+	}
+}
+
+impl<T: Coefficient> Eq for Term<T> {}
+
+/// Sorts component names before upper case constants and ignores underscores like [`VarValue`].
+type VarKey = (bool, String);
+
+fn var_key(var: &VarValue) -> VarKey {
+	(var.starts_with(|c: char| c.is_ascii_uppercase()), var.replace('_', ""))
+}
+
+/// A product of variables that identifies a coefficient of a normalized term.
+///
+/// Formats as `constant` for the empty product and otherwise like `n * READ`.
+#[derive(Clone, Debug)]
+pub struct Monomial {
+	key: MonomialKey,
+	vars: Vec<String>,
+}
+
+impl Monomial {
+	pub fn vars(&self) -> &[String] {
+		&self.vars
+	}
+
+	pub fn is_constant(&self) -> bool {
+		self.vars.is_empty()
+	}
+}
+
+impl fmt::Display for Monomial {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if self.is_constant() {
+			write!(f, "constant")
+		} else {
+			write!(f, "{}", self.vars.join(" * "))
+		}
+	}
+}
+
+impl Serialize for Monomial {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl PartialEq for Monomial {
+	fn eq(&self, other: &Self) -> bool {
+		self.key == other.key
+	}
+}
+
+impl Eq for Monomial {}
+
+impl PartialOrd for Monomial {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Monomial {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		self.key.cmp(&other.key)
+	}
+}
+
+/// Sorts monomials by their degree and then by their variables.
+type MonomialKey = (usize, Vec<VarKey>);
+
+fn monomial_key(vars: &[VarValue]) -> MonomialKey {
+	(vars.len(), vars.iter().map(var_key).collect())
+}
+
+/// Monomials of a polynomial by their sorted variables.
+type Polynomial<T> = Map<MonomialKey, (Vec<VarValue>, Coeff<T>)>;
+
+fn add_monomial<T: Coefficient>(
+	polynomial: &mut Polynomial<T>,
+	key: MonomialKey,
+	vars: Vec<VarValue>,
+	coeff: Coeff<T>,
+) -> Option<()> {
+	match polynomial.entry(key) {
+		Entry::Vacant(e) => {
+			e.insert((vars, coeff));
+		},
+		Entry::Occupied(mut e) => {
+			let sum = e.get().1.add(&coeff)?;
+			e.get_mut().1 = sum;
+		},
+	}
+	Some(())
+}
+
+/// Coefficient of a monomial; stays a scalar as long as no value is involved.
+#[derive(Clone, Debug)]
+enum Coeff<T> {
+	Scalar(u128),
+	Value(T),
+}
+
+impl<T: Coefficient> Coeff<T> {
+	fn add(&self, other: &Self) -> Option<Self> {
+		match (self, other) {
+			(Self::Scalar(a), Self::Scalar(b)) => a.checked_add(*b).map(Self::Scalar),
+			(Self::Value(a), Self::Value(b)) => a.try_add(b).map(Self::Value),
+			(Self::Scalar(s), Self::Value(v)) | (Self::Value(v), Self::Scalar(s)) =>
+				T::try_from_scalar(*s)?.try_add(v).map(Self::Value),
+		}
+	}
+
+	fn mul(&self, other: &Self) -> Option<Self> {
+		match (self, other) {
+			(Self::Scalar(a), Self::Scalar(b)) => a.checked_mul(*b).map(Self::Scalar),
+			(Self::Value(a), Self::Value(b)) => a.try_mul(b).map(Self::Value),
+			(Self::Scalar(s), Self::Value(v)) | (Self::Value(v), Self::Scalar(s)) =>
+				v.try_scale(*s).map(Self::Value),
+		}
+	}
+
+	fn is_zero(&self) -> bool {
+		match self {
+			Self::Scalar(s) => *s == 0,
+			Self::Value(v) => v == &T::zero(),
+		}
+	}
+
+	fn into_term(self) -> Term<T> {
+		match self {
+			Self::Scalar(s) => Term::Scalar(s),
+			Self::Value(v) => Term::Value(v),
+		}
+	}
+}
+
+/// Scalars and values are the same if the value type is a plain number.
+impl<T: Coefficient> PartialEq for Coeff<T> {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Scalar(a), Self::Scalar(b)) => a == b,
+			(Self::Value(a), Self::Value(b)) => a == b,
+			(Self::Scalar(s), Self::Value(v)) | (Self::Value(v), Self::Scalar(s)) =>
+				T::try_from_scalar(*s).as_ref() == Some(v),
+		}
 	}
 }
 
 impl TryInto<SimpleTerm> for &ExprBinary {
-	type Error = String;
+	type Error = Error;
 
 	fn try_into(self) -> Result<SimpleTerm, Self::Error> {
 		let left = crate::parse::pallet::parse_scalar_expression(&self.left)?.into();
@@ -471,7 +738,7 @@ impl TryInto<SimpleTerm> for &ExprBinary {
 		let term = match self.op {
 			BinOp::Mul(_) => SimpleTerm::Mul(left, right),
 			BinOp::Add(_) => SimpleTerm::Add(left, right),
-			_ => return Err("Unexpected operator".into()),
+			_ => return Err(Error::parse("Unexpected operator")),
 		};
 		Ok(term)
 	}
@@ -1,19 +1,21 @@
 #[cfg(test)]
 use rstest::*;
 
-use crate::{parse::pallet::*, scope::*, term::*, *};
+use crate::{
+	parse::pallet::*,
+	scope::*,
+	term::*,
+	test::{extrinsic, worst::extend_scoped_components},
+	traits::Weight,
+	*,
+};
 use maplit::hashmap;
 
 #[test]
 fn extend_scoped_components_works() {
 	// One component without range
 	{
-		let a = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("a"),
-			comp_ranges: None,
-		};
+		let a = extrinsic("", "", var!("a"));
 		let base = SimpleScope::empty();
 
 		// base
@@ -70,12 +72,7 @@ fn extend_scoped_components_works() {
 	{
 		let mut comp_ranges = HashMap::new();
 		comp_ranges.insert("a".into(), ComponentRange { min: 0, max: 200 });
-		let a = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("a"),
-			comp_ranges: Some(comp_ranges),
-		};
+		let a = SimpleExtrinsic { comp_ranges: Some(comp_ranges), ..extrinsic("", "", var!("a")) };
 		let base = SimpleScope::empty();
 
 		// base
@@ -145,18 +142,8 @@ fn extend_scoped_components_works() {
 	}
 	// Two components without ranges
 	{
-		let a = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("a"),
-			comp_ranges: None,
-		};
-		let b = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("b"),
-			comp_ranges: None,
-		};
+		let a = extrinsic("", "", var!("a"));
+		let b = extrinsic("", "", var!("b"));
 		let base = SimpleScope::empty();
 
 		// base
@@ -190,17 +177,10 @@ fn extend_scoped_components_works() {
 		let mut comp_ranges = HashMap::new();
 		comp_ranges.insert("a".into(), ComponentRange { min: 0, max: 200 });
 		let a = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("a"),
 			comp_ranges: Some(comp_ranges.clone()),
+			..extrinsic("", "", var!("a"))
 		};
-		let b = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("b"),
-			comp_ranges: Some(comp_ranges),
-		};
+		let b = SimpleExtrinsic { comp_ranges: Some(comp_ranges), ..extrinsic("", "", var!("b")) };
 		let base = SimpleScope::empty();
 
 		// base
@@ -235,16 +215,12 @@ fn extend_scoped_components_works() {
 		comp_ranges.insert("a".into(), ComponentRange { min: 0, max: 200 });
 		comp_ranges.insert("b".into(), ComponentRange { min: 0, max: 200 });
 		let a = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("a"),
 			comp_ranges: Some(comp_ranges.clone()),
+			..extrinsic("", "", var!("a"))
 		};
 		let b = SimpleExtrinsic {
-			name: "".into(),
-			pallet: "".into(),
-			term: var!("b"),
 			comp_ranges: Some(comp_ranges.clone()),
+			..extrinsic("", "", var!("b"))
 		};
 		let base = SimpleScope::empty();
 
@@ -341,34 +317,23 @@ fn compare_extrinsics_works(
 	#[case] expected: &[(CompareMethod, RelativeChange, f64)],
 ) {
 	let old = SimpleExtrinsic {
-		name: "".into(),
-		pallet: "".into(),
-		term: old,
 		// n=100, l=255
 		comp_ranges: Some(hashmap! {
 			"n".into() => ComponentRange { min: 0, max: 100 },
 			"l".into() => ComponentRange { min: 0, max: 255 },
 		}),
+		..extrinsic("", "", old)
 	};
 	let new = SimpleExtrinsic {
-		name: "".into(),
-		pallet: "".into(),
-		term: new,
 		// n=100, l=255
 		comp_ranges: Some(hashmap! {
 			"n".into() => ComponentRange { min: 0, max: 100 },
 			"l".into() => ComponentRange { min: 0, max: 255 },
 		}),
+		..extrinsic("", "", new)
 	};
 	for expected in expected {
-		let params = CompareParams {
-			method: expected.0,
-			unit: Dimension::Time,
-			ignore_errors: false,
-			git_pull: false,
-			offline: true,
-			git_force: false,
-		};
+		let params = CompareParams { method: expected.0, ..Default::default() };
 
 		let change = compare_extrinsics(Some(old.clone()), Some(new.clone()), &params).unwrap();
 		assert_eq!(change.change, expected.1);
@@ -418,6 +383,12 @@ fn filter_rel_threshold_works(
 		name: String::new(),
 		file: String::new(),
 		change: TermDiff::Changed(mocked_change(old, new)),
+		chromatic: None,
+		storage: vec![],
+		summary: None,
+		coefficients: vec![],
+		renamed_from: None,
+		metadata: vec![],
 	}];
 	let params = FilterParams { threshold, ..Default::default() };
 
@@ -442,5 +413,630 @@ fn mocked_change(old: u128, new: u128) -> TermChange {
 		percent: percent(old, new),
 		change: RelativeChange::Changed,
 		method: CompareMethod::GuessWorst,
+		absolute: None,
+	}
+}
+
+#[rstest]
+#[case(DimensionSelector::Time, 10.0)]
+#[case(DimensionSelector::Proof, 100.0)]
+#[case(DimensionSelector::Worst, 100.0)]
+fn compare_files_chromatic_works(#[case] selector: DimensionSelector, #[case] percent: f64) {
+	let ext = |time: u128, proof: u128| extrinsic("pallet", "ext", cval!((time, proof).into()));
+	let params = CompareParams { chromatic: Some(selector), ..Default::default() };
+
+	let diff =
+		compare_files(vec![ext(100, 100)], vec![ext(110, 200)], &params, &FilterParams::default())
+			.unwrap();
+	assert_eq!(diff.len(), 1);
+
+	let chromatic = diff[0].chromatic.as_ref().unwrap();
+	assert_eq!(chromatic.time.term().unwrap().new_v, Some(110));
+	assert_eq!(chromatic.proof.term().unwrap().new_v, Some(200));
+	assert!((diff[0].term().unwrap().percent - percent).abs() < 0.001);
+}
+
+#[rstest]
+#[case(None, None)]
+#[case(Some(1_000), None)]
+#[case(Some(100), Some("Worst case 110ps exceeds 75% of the max block time (75ps) at n=10"))]
+fn check_block_budget_works(#[case] max_block_time: Option<u128>, #[case] want: Option<&str>) {
+	let ext = SimpleExtrinsic {
+		comp_ranges: Some(hashmap! {
+			"n".into() => ComponentRange { min: 0, max: 10 },
+		}),
+		..extrinsic("", "", add!(scalar!(10), mul!(scalar!(10), var!("n"))))
+	};
+	let params = CompareParams {
+		budget: BudgetParams { max_block_time, ..Default::default() },
+		..Default::default()
+	};
+
+	assert_eq!(check_block_budget(&ext, &params).unwrap(), want.map(Into::into));
+}
+
+#[rstest]
+#[case(1, None)]
+#[case(2, Some("weight saturates at u64::MAX with n=2"))]
+fn check_saturation_works(#[case] max: u32, #[case] want: Option<&str>) {
+	let ext = SimpleExtrinsic {
+		comp_ranges: Some(hashmap! {
+			"n".into() => ComponentRange { min: 0, max },
+		}),
+		..extrinsic(
+			"",
+			"",
+			add!(scalar!(10), mul!(scalar!(u32::MAX), mul!(scalar!(u32::MAX), var!("n")))),
+		)
+	};
+	let params = CompareParams { method: CompareMethod::ExactWorst, ..Default::default() };
+
+	assert_eq!(check_saturation(&ext, Revision::New, &params).unwrap(), want.map(Into::into));
+}
+
+#[rstest]
+#[case(CompareMethod::Base, (50_000_012, 6))]
+#[case(CompareMethod::GuessWorst, (50_000_030, 15))]
+#[case(CompareMethod::ExactWorst, (50_000_030, 15))]
+fn evaluate_extrinsic_works(#[case] method: CompareMethod, #[case] want: (u128, u128)) {
+	let ext = ChromaticExtrinsic {
+		comp_ranges: Some(hashmap! {
+			"n".into() => ComponentRange { min: 1, max: 10 },
+		}),
+		..extrinsic(
+			"pallet",
+			"ext",
+			cadd!(
+				cadd!(cval!((10, 5).into()), cmul!(cval!((2, 1).into()), cvar!("n"))),
+				cmul!(Term::Scalar(2), cvar!("READ"))
+			),
+		)
+	};
+
+	assert_eq!(evaluate_extrinsic(&ext, method, Default::default()).unwrap(), want.into());
+}
+
+/// The warning is kept when the extrinsic is also over budget.
+#[rstest]
+#[case(None)]
+#[case(Some(1_000))]
+fn compare_files_warns_on_saturation(#[case] max_block_time: Option<u128>) {
+	let ext = |factor: u128| ChromaticExtrinsic {
+		comp_ranges: Some(hashmap! {
+			"n".into() => ComponentRange { min: 0, max: 1_000 },
+		}),
+		..extrinsic("pallet", "ext", cmul!(cval!((factor, 0).into()), cvar!("n")))
+	};
+	let params = CompareParams {
+		method: CompareMethod::ExactWorst,
+		budget: BudgetParams { max_block_time, ..Default::default() },
+		..Default::default()
+	};
+
+	let diff = compare_files(
+		vec![ext(1)],
+		vec![ext(u64::MAX as u128 / 10)],
+		&params,
+		&FilterParams::default(),
+	)
+	.unwrap();
+	assert_eq!(
+		diff[0].warnings().first().map(|w| w.as_str()),
+		Some("New weight saturates at u64::MAX with n=1.00K: pallet::ext")
+	);
+	assert_eq!(diff[0].term().unwrap().new_v, Some(u64::MAX as u128));
+	assert_eq!(diff[0].over_budget().len(), max_block_time.is_some() as usize);
+	assert_eq!(FailOn::Warning.offenders(&diff).len(), 1);
+
+	let json = serde_json::to_value(&diff[0].change).unwrap();
+	let variant = if max_block_time.is_some() { "over-budget" } else { "warning" };
+	assert_eq!(json[variant]["change"]["new_v"], u64::MAX);
+	assert_eq!(
+		json[variant]["warnings"],
+		serde_json::json!(["New weight saturates at u64::MAX with n=1.00K: pallet::ext"])
+	);
+	assert_eq!(json[variant]["message"].is_string(), max_block_time.is_some());
+}
+
+#[rstest]
+#[case("0.5", Ok(0.5))]
+#[case("1", Ok(1.0))]
+#[case("0", Err("Ratio must be in (0, 1] but is 0"))]
+#[case("1.5", Err("Ratio must be in (0, 1] but is 1.5"))]
+#[case("-1", Err("Ratio must be in (0, 1] but is -1"))]
+fn parse_ratio_works(#[case] input: &str, #[case] want: std::result::Result<f64, &str>) {
+	assert_eq!(parse_ratio(input), want.map_err(String::from));
+}
+
+/// Overhead and storage weight files are compared like pallets.
+#[test]
+fn compare_files_overhead_works() {
+	let olds = crate::parse::parse_files(
+		&[
+			"../test_data/new/extrinsic_weights.rs.txt".into(),
+			"../test_data/new/rocksdb_weights.rs.txt".into(),
+		],
+		&Default::default(),
+	)
+	.unwrap();
+	let news = crate::parse::parse_files(
+		&[
+			"../test_data/chromatic/extrinsic_weights.rs.txt".into(),
+			"../test_data/new/rocksdb_weights.rs.txt".into(),
+		],
+		&Default::default(),
+	)
+	.unwrap();
+	let params = CompareParams { method: CompareMethod::ExactWorst, ..Default::default() };
+
+	let diff = compare_files(olds, news, &params, &FilterParams::default()).unwrap();
+	let got = diff
+		.iter()
+		.map(|d| {
+			let t = d.term().unwrap();
+			(d.file.as_str(), d.name.as_str(), t.change, t.old_v, t.new_v)
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(
+		got,
+		vec![
+			(
+				"extrinsic_weights.rs.txt",
+				"ExtrinsicBaseWeight",
+				RelativeChange::Changed,
+				Some(85_212_000),
+				Some(99_840_000)
+			),
+			(
+				"rocksdb_weights.rs.txt",
+				"read",
+				RelativeChange::Unchanged,
+				Some(25_000),
+				Some(25_000)
+			),
+			(
+				"rocksdb_weights.rs.txt",
+				"write",
+				RelativeChange::Unchanged,
+				Some(100_000),
+				Some(100_000)
+			),
+		]
+	);
+}
+
+#[rstest]
+#[case("failed", Ok(FailOn::Failed))]
+#[case("warning", Ok(FailOn::Warning))]
+#[case("changed", Ok(FailOn::Changed(0.0)))]
+#[case("changed:10", Ok(FailOn::Changed(10.0)))]
+#[case("changed:2.5%", Ok(FailOn::Changed(2.5)))]
+#[case("added", Ok(FailOn::Added))]
+#[case("removed", Ok(FailOn::Removed))]
+#[case("changed:x", Err(()))]
+#[case("removed:10", Err(()))]
+#[case("other", Err(()))]
+fn fail_on_parse_works(#[case] input: &str, #[case] want: std::result::Result<FailOn, ()>) {
+	let got = input.parse::<FailOn>().map_err(|_| ());
+	assert_eq!(got, want);
+
+	if let Ok(policy) = got {
+		assert_eq!(policy.to_string().parse::<FailOn>(), Ok(policy));
+	}
+}
+
+#[rstest]
+#[case(FailOn::Failed, vec![])]
+#[case(FailOn::Changed(0.0), vec!["changed", "worse"])]
+#[case(FailOn::Changed(20.0), vec!["worse"])]
+#[case(FailOn::Added, vec!["added"])]
+#[case(FailOn::Removed, vec!["removed"])]
+fn fail_on_offenders_works(#[case] policy: FailOn, #[case] want: Vec<&str>) {
+	let ext = |name: &str, weight: u128| extrinsic("pallet", name, cval!((weight, 0).into()));
+	let olds = vec![ext("changed", 100), ext("worse", 100), ext("better", 100), ext("removed", 1)];
+	let news = vec![ext("changed", 110), ext("worse", 150), ext("better", 50), ext("added", 1)];
+	let params = CompareParams::default();
+
+	let diff = compare_files(olds, news, &params, &FilterParams::default()).unwrap();
+	let mut got = policy.offenders(&diff).iter().map(|d| d.name.as_str()).collect::<Vec<_>>();
+	got.sort();
+	assert_eq!(got, want);
+}
+
+/// Warnings and errors of the dimension that was not selected still violate the policies.
+#[test]
+fn fail_on_offenders_chromatic_works() {
+	let ext = |proof: u128| ChromaticExtrinsic {
+		comp_ranges: Some(hashmap! {
+			"n".into() => ComponentRange { min: 0, max: 1_000 },
+		}),
+		..extrinsic("pallet", "ext", cmul!(cval!((1, proof).into()), cvar!("n")))
+	};
+	let params = CompareParams {
+		method: CompareMethod::ExactWorst,
+		chromatic: Some(DimensionSelector::Time),
+		..Default::default()
+	};
+
+	let diff = compare_files(
+		vec![ext(1)],
+		vec![ext(u64::MAX as u128 / 10)],
+		&params,
+		&FilterParams::default(),
+	)
+	.unwrap();
+	assert!(matches!(diff[0].change, TermDiff::Changed(_)));
+	assert_eq!(FailOn::Warning.offenders(&diff).len(), 1);
+	assert!(FailOn::Failed.offenders(&diff).is_empty());
+}
+
+/// A change of only the proof violates `changed` although the time is selected.
+#[rstest]
+#[case(FailOn::Changed(10.0), 1)]
+#[case(FailOn::Changed(60.0), 0)]
+#[case(FailOn::Added, 0)]
+fn fail_on_offenders_chromatic_proof_works(#[case] policy: FailOn, #[case] want: usize) {
+	let ext = |proof: u128| extrinsic("pallet", "ext", cval!((100, proof).into()));
+	let params = CompareParams { chromatic: Some(DimensionSelector::Time), ..Default::default() };
+
+	let diff =
+		compare_files(vec![ext(100)], vec![ext(150)], &params, &FilterParams::default()).unwrap();
+	assert_eq!(diff[0].term().unwrap().change, RelativeChange::Unchanged);
+	assert_eq!(policy.offenders(&diff).len(), want);
+}
+
+#[test]
+fn compare_storage_works() {
+	let old = r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		/// Storage: Staking Ledger (r:1 w:1)
+		/// Proof: Staking Ledger (max_values: None, max_size: Some(1091), added: 3566, mode: MaxEncodedLen)
+		/// Storage: Staking Payee (r:0 w:1)
+		/// Storage: Staking Bonded (r:1 w:0)
+		fn ext() -> Weight {
+			Weight::from_parts(5, 0)
+		}
+	}"#;
+	let new = r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		/// Storage: Staking Ledger (r:1 w:2)
+		/// Proof: Staking Ledger (max_values: None, max_size: Some(2000), added: 3566, mode: MaxEncodedLen)
+		/// Storage: System Account (r:1 w:0)
+		/// Storage: Staking Bonded (r:1 w:0)
+		fn ext() -> Weight {
+			Weight::from_parts(5, 0)
+		}
+	}"#;
+	let old = parse::pallet::parse_content("".into(), old.into(), &Default::default()).unwrap();
+	let new = parse::pallet::parse_content("".into(), new.into(), &Default::default()).unwrap();
+
+	let changes = compare_storage(old.first(), new.first());
+	let got = changes.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+	assert_eq!(
+		got,
+		vec![
+			"~Staking::Ledger (r:1 w:1->2 max_size:1091->2000)",
+			"-Staking::Payee (r:0 w:1)",
+			"+System::Account (r:1 w:0)",
+		]
+	);
+	assert_eq!(
+		changes.iter().map(|c| c.change()).collect::<Vec<_>>(),
+		vec![RelativeChange::Changed, RelativeChange::Removed, RelativeChange::Added]
+	);
+
+	let params = CompareParams::default();
+	let diff = compare_files(old, new, &params, &FilterParams::default()).unwrap();
+	assert_eq!(diff[0].storage, changes);
+}
+
+#[rstest]
+#[case(add!(scalar!(10), mul!(scalar!(5), var!("n"))), RelativeChange::Unchanged)]
+#[case(add!(mul!(var!("n"), scalar!(5)), scalar!(10)), RelativeChange::Unchanged)]
+#[case(add!(add!(scalar!(4), mul!(scalar!(5), var!("n"))), scalar!(6)), RelativeChange::Unchanged)]
+#[case(add!(scalar!(10), mul!(scalar!(6), var!("n"))), RelativeChange::Changed)]
+fn compare_terms_normalizes(#[case] new: SimpleTerm, #[case] change: RelativeChange) {
+	let old = add!(scalar!(10), mul!(scalar!(5), var!("n")));
+	let scope = SimpleScope::empty().with_var("n", scalar!(3));
+
+	let got = compare_terms(Some(&old), Some(&new), CompareMethod::Base, &scope).unwrap();
+	assert_eq!(got.change, change);
+}
+
+#[test]
+fn compare_coefficients_works() {
+	let old = add!(add!(scalar!(10), mul!(scalar!(5), var!("n"))), mul!(scalar!(2), var!("READ")));
+	let new = add!(add!(mul!(var!("n"), scalar!(6)), scalar!(10)), mul!(var!("n"), var!("READ")));
+
+	let got = compare_coefficients(Some(&old), Some(&new))
+		.into_iter()
+		.map(|c| (c.monomial.to_string(), c.change.old, c.change.new, c.change.change))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		got,
+		vec![
+			("constant".into(), Some(10), Some(10), RelativeChange::Unchanged),
+			("n".into(), Some(5), Some(6), RelativeChange::Changed),
+			("READ".into(), Some(2), None, RelativeChange::Removed),
+			("n * READ".into(), None, Some(1), RelativeChange::Added),
+		]
+	);
+}
+
+#[test]
+fn compare_coefficients_added_works() {
+	let new = add!(scalar!(10), mul!(scalar!(5), var!("n")));
+
+	let got = compare_coefficients(None, Some(&new));
+	assert_eq!(got.len(), 2);
+	assert!(got.iter().all(|c| c.change.change == RelativeChange::Added));
+	assert_eq!(
+		serde_json::to_value(&got[1]).unwrap(),
+		serde_json::json!({"monomial": "n", "old": null, "new": 5, "change": "added", "percent": null})
+	);
+}
+
+#[rstest]
+#[case(Some(10), Some(15), RelativeChange::Changed, 50.0)]
+#[case(Some(10), Some(10), RelativeChange::Unchanged, 0.0)]
+#[case(None, Some(10), RelativeChange::Added, f64::INFINITY)]
+#[case(Some(10), None, RelativeChange::Removed, -100.0)]
+fn summary_diff_works(
+	#[case] old: Option<u128>,
+	#[case] new: Option<u128>,
+	#[case] change: RelativeChange,
+	#[case] percent: Percent,
+) {
+	let ext = |min_time: Option<u128>| SimpleExtrinsic {
+		summary: BenchmarkSummary { min_time, ..Default::default() },
+		..extrinsic("", "", scalar!(1))
+	};
+	let (old, new) = (ext(old), ext(new));
+
+	let diff = SummaryDiff::new(Some(&old), Some(&new)).unwrap();
+	let got = diff.compare(|s| s.min_time).unwrap();
+	assert_eq!(got.change, change);
+	assert_eq!(got.percent, percent);
+	assert!(diff.compare(|s| s.measured_proof).is_none());
+}
+
+#[test]
+fn summary_diff_empty_works() {
+	let ext: SimpleExtrinsic = extrinsic("", "", scalar!(1));
+
+	assert_eq!(SummaryDiff::new(Some(&ext), Some(&ext)), None);
+	assert_eq!(SummaryDiff::new(None, Some(&ext)), None);
+}
+
+#[rstest]
+#[case(Some(100), Some(150), Some(50.0))]
+#[case(Some(100), Some(100), Some(0.0))]
+#[case(Some(200), Some(100), Some(-50.0))]
+#[case(None, Some(100), None)]
+#[case(Some(100), None, None)]
+fn proof_estimation_works(
+	#[case] measured_proof: Option<u128>,
+	#[case] estimated_proof: Option<u128>,
+	#[case] percent: Option<Percent>,
+) {
+	let summary = BenchmarkSummary { measured_proof, estimated_proof, ..Default::default() };
+	assert_eq!(summary.proof_estimation().map(|c| c.percent), percent);
+
+	// The new summary is preferred over the old one.
+	let old =
+		BenchmarkSummary { measured_proof: Some(1), estimated_proof: Some(1), min_time: None };
+	let diff = SummaryDiff { old: Some(old.clone()), new: Some(summary) };
+	assert_eq!(diff.proof_estimation().map(|c| c.percent), percent);
+	let diff = SummaryDiff { old: Some(old), new: None };
+	assert_eq!(diff.proof_estimation().map(|c| c.percent), Some(0.0));
+}
+
+#[rstest]
+#[case("../test_data/new/rocksdb_weights.rs.txt", 25_000, 100_000)]
+#[case("../test_data/new/paritydb_weights.rs.txt", 8, 50)]
+fn db_weights_from_file_works(#[case] file: PathBuf, #[case] read: u128, #[case] write: u128) {
+	assert_eq!(DbWeights::from_file(&file).unwrap(), DbWeights { read, write });
+}
+
+#[test]
+fn db_params_load_works() {
+	let mut db = DbParams {
+		db_weights: Some("new/rocksdb_weights.rs.txt".into()),
+		new_db_weights: Some("new/paritydb_weights.rs.txt".into()),
+		write: Some(7),
+		..Default::default()
+	};
+	db.load(Revision::Old, Path::new("../test_data")).unwrap();
+	db.load(Revision::New, Path::new("../test_data")).unwrap();
+
+	assert_eq!(db.weights(Revision::Old), DbWeights { read: 25_000, write: 7 });
+	assert_eq!(db.weights(Revision::New), DbWeights { read: 8, write: 7 });
+}
+
+/// Each revision is evaluated with its own DB weights.
+#[rstest]
+#[case(Dimension::Time, (100, 200), RelativeChange::Changed, 210, 410)]
+#[case(Dimension::Time, (100, 100), RelativeChange::Unchanged, 210, 210)]
+#[case(Dimension::Proof, (100, 200), RelativeChange::Unchanged, 10, 10)]
+fn compare_extrinsics_db_weights_works(
+	#[case] unit: Dimension,
+	#[case] (old_read, new_read): (u128, u128),
+	#[case] change: RelativeChange,
+	#[case] old_v: u128,
+	#[case] new_v: u128,
+) {
+	let ext = extrinsic("pallet", "ext", add!(scalar!(10), reads!(scalar!(2))));
+	let params = CompareParams {
+		unit,
+		db: DbParams {
+			old: DbWeights { read: old_read, write: 0 },
+			new: DbWeights { read: new_read, write: 0 },
+			..Default::default()
+		},
+		..Default::default()
+	};
+
+	let got = compare_extrinsics(Some(ext.clone()), Some(ext.clone()), &params).unwrap();
+	assert_eq!(got.change, change);
+	assert_eq!((got.old_v, got.new_v), (Some(old_v), Some(new_v)));
+	// The terms themselves are kept for display.
+	if unit == Dimension::Time {
+		assert_eq!(got.old, Some(ext.term));
 	}
 }
+
+#[test]
+fn overhead_params_load_works() {
+	let mut overhead = OverheadParams {
+		extrinsic_base: Some("new/extrinsic_weights.rs.txt".into()),
+		new_extrinsic_base: Some("chromatic/extrinsic_weights.rs.txt".into()),
+		..Default::default()
+	};
+	overhead.load(Revision::Old, Path::new("../test_data")).unwrap();
+	overhead.load(Revision::New, Path::new("../test_data")).unwrap();
+
+	assert_eq!(overhead.weight(Revision::Old), Some(Weight { time: 85_212_000, proof: 0 }));
+	assert_eq!(overhead.weight(Revision::New), Some(Weight { time: 99_840_000, proof: 0 }));
+}
+
+#[test]
+fn overhead_params_load_block_weight_errors() {
+	let mut overhead = OverheadParams {
+		extrinsic_base: Some("../test_data/new/block_weights.rs.txt".into()),
+		..Default::default()
+	};
+
+	let err = overhead.load(Revision::Old, Path::new(".")).unwrap_err();
+	assert_eq!(
+		err,
+		Error::InvalidParam {
+			msg: "Expected an ExtrinsicBaseWeight but found a BlockExecutionWeight in \
+			      ./../test_data/new/block_weights.rs.txt"
+				.into()
+		}
+	);
+}
+
+/// The `ExtrinsicBaseWeight` of each revision is added to the extrinsics of the pallets.
+#[test]
+fn compare_files_extrinsic_base_works() {
+	let files = |overhead: &str| {
+		crate::parse::parse_files(
+			&[
+				"../test_data/new/pallet_staking.rs.txt".into(),
+				format!("../test_data/{}/extrinsic_weights.rs.txt", overhead).into(),
+			],
+			&Default::default(),
+		)
+		.unwrap()
+	};
+	let params = CompareParams {
+		overhead: OverheadParams {
+			old: Some(Weight { time: 100, proof: 0 }),
+			new: Some(Weight { time: 300, proof: 0 }),
+			..Default::default()
+		},
+		..Default::default()
+	};
+	let filter = FilterParams {
+		extrinsic: Some("^(bond|ExtrinsicBaseWeight)$".into()),
+		..Default::default()
+	};
+
+	let diff = compare_files(files("new"), files("chromatic"), &params, &filter).unwrap();
+	let got = diff
+		.iter()
+		.map(|d| (d.name.as_str(), d.term().unwrap().old_v, d.term().unwrap().new_v))
+		.collect::<Vec<_>>();
+	assert_eq!(
+		got,
+		vec![
+			// The overhead file itself is not changed.
+			("ExtrinsicBaseWeight", Some(85_212_000), Some(99_840_000)),
+			("bond", Some(540_601_100), Some(540_601_300)),
+		]
+	);
+}
+
+/// The pallets of both runtimes are matched by file name.
+#[test]
+fn compare_runtime_dirs_works() {
+	let params = CompareParams::default();
+	let filter = FilterParams { threshold: 0.0, ..Default::default() };
+	let compare = |pattern: &str| {
+		compare_runtime_dirs(
+			Path::new(".."),
+			None,
+			Path::new("test_data/old"),
+			Path::new("test_data/new"),
+			&params,
+			&filter,
+			pattern,
+		)
+	};
+
+	let diff = compare("pallet_*.rs.txt").unwrap();
+	let bond = diff.iter().find(|d| d.name == "bond").unwrap();
+	assert_eq!(bond.file, "pallet_staking.rs.txt");
+	assert_eq!(bond.term().unwrap().change, RelativeChange::Changed);
+	assert_eq!(
+		diff.iter()
+			.filter(|d| d.term().unwrap().change == RelativeChange::Added)
+			.count(),
+		1
+	);
+
+	let err = compare("*_weights.rs.txt").err().unwrap();
+	assert_eq!(err.to_string(), "No files in test_data/old match the path pattern");
+	assert!(compare("../*.rs").is_err());
+}
+
+#[test]
+fn compare_runtime_dirs_rejects_escaping_dirs() {
+	let params = CompareParams::default();
+	let filter = FilterParams::default();
+	let staking = Path::new("test_data/new");
+	for dir in [Path::new("test_data/../test_data/old"), Path::new("/tmp")] {
+		for (left, right) in [(dir, staking), (staking, dir)] {
+			let err = compare_runtime_dirs(
+				Path::new(".."),
+				None,
+				left,
+				right,
+				&params,
+				&filter,
+				"*.rs.txt",
+			)
+			.err()
+			.unwrap();
+			assert!(matches!(err, Error::InvalidParam { .. }), "{:?}", err);
+		}
+	}
+}
+
+/// Files with the same name in different subdirectories are different pallets.
+#[test]
+fn compare_runtime_dirs_keys_by_relative_path() {
+	let repo = std::env::temp_dir().join("compare_runtime_dirs_keys_by_relative_path");
+	let staking = std::fs::read_to_string("../test_data/new/pallet_staking.rs.txt").unwrap();
+	for side in ["left", "right"] {
+		for sub in ["x", "y"] {
+			let dir = repo.join(side).join(sub);
+			std::fs::create_dir_all(&dir).unwrap();
+			std::fs::write(dir.join("pallet.rs"), &staking).unwrap();
+		}
+	}
+
+	let params = CompareParams::default();
+	let filter = FilterParams { threshold: 0.0, ..Default::default() };
+	let diff = compare_runtime_dirs(
+		&repo,
+		None,
+		Path::new("left"),
+		Path::new("right"),
+		&params,
+		&filter,
+		"**/*.rs",
+	)
+	.unwrap();
+	let bonds = diff.iter().filter(|d| d.name == "bond").map(|d| d.file.as_str());
+	assert_eq!(bonds.collect::<Vec<_>>(), vec!["x/pallet.rs", "y/pallet.rs"]);
+}
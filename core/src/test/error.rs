@@ -0,0 +1,131 @@
+use rstest::*;
+use std::path::Path;
+
+use crate::{
+	compare_terms,
+	parse::{pallet::parse_expression, read_file},
+	scope::SimpleScope,
+	test::{extrinsic, worst::extend_scoped_components},
+	var, CompareMethod, Error,
+};
+
+#[test]
+fn missing_range_errors() {
+	let ext = extrinsic("pallet", "ext", var!("a"));
+	let err = extend_scoped_components(
+		Some(&ext),
+		None,
+		CompareMethod::ExactWorst,
+		&SimpleScope::empty(),
+	)
+	.unwrap_err();
+
+	assert_eq!(err, Error::MissingRange { component: "a".into(), extrinsic: "pallet::ext".into() });
+	assert_eq!(
+		err.to_string(),
+		"No range for component a of call pallet::ext - use Guess instead!"
+	);
+}
+
+#[test]
+fn eval_errors() {
+	let err = compare_terms(Some(&var!("a")), None, CompareMethod::Base, &SimpleScope::empty())
+		.unwrap_err();
+
+	assert_eq!(err, Error::Eval { msg: "Variable 'a' not found".into() });
+}
+
+#[rstest]
+#[case("foo(5)", "foo")]
+#[case("Weight::from_foo(5)", "Weight::from_foo")]
+#[case("(5 as Weight).bar(1)", "bar")]
+fn unknown_call_errors(#[case] input: &str, #[case] name: &str) {
+	let expr = syn::parse_str::<syn::Expr>(input).unwrap();
+	let err = parse_expression(&expr).unwrap_err();
+
+	assert!(
+		matches!(err, Error::UnknownCall { name: ref n, span: Some(_), .. } if n == name),
+		"{:?}",
+		err
+	);
+}
+
+#[test]
+fn io_errors() {
+	let err = read_file(Path::new("does/not/exist.rs")).unwrap_err();
+
+	assert!(matches!(err, Error::Io { ref file, .. } if file == "does/not/exist.rs"), "{:?}", err);
+}
+
+#[test]
+fn parse_errors_have_file() {
+	let path = Path::new("../test_data/new/rocksdb_weights.rs.txt");
+	let err = crate::parse::pallet::parse_pallet_file(path, &Default::default()).unwrap_err();
+
+	assert!(
+		matches!(err, Error::Parse { file: Some(ref f), .. } if f == &path.display().to_string())
+	);
+	assert!(err.to_string().starts_with("../test_data/new/rocksdb_weights.rs.txt: "));
+}
+
+const BAD_WEIGHT: &str = "impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn foo() -> Weight {
+		Weight::from_ref_time(5 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.frobnicate(2)
+	}
+}
+";
+
+#[test]
+fn parse_errors_have_span() {
+	let err = crate::parse::pallet::parse_content(
+		"pallet".into(),
+		BAD_WEIGHT.into(),
+		&Default::default(),
+	)
+	.unwrap_err();
+
+	let span = match err {
+		Error::UnknownCall { ref name, span: Some(span), .. } if name == "frobnicate" => span,
+		_ => panic!("Unexpected error: {:?}", err),
+	};
+	assert_eq!((span.line, span.column), (5, 4));
+	assert_eq!((span.end_line, span.end_column), (5, 14));
+}
+
+#[test]
+fn parse_errors_display_location() {
+	let err = crate::parse::pallet::parse_content(
+		"pallet".into(),
+		BAD_WEIGHT.into(),
+		&Default::default(),
+	)
+	.unwrap_err()
+	.in_file(Path::new("weights.rs"));
+
+	assert_eq!(err.location().map(|(f, _)| f), Some("weights.rs"));
+	assert_eq!(err.to_string(), "weights.rs:5:5: Unknown call: frobnicate");
+}
+
+#[test]
+fn syn_errors_have_span() {
+	let err = crate::parse::pallet::parse_content(
+		"pallet".into(),
+		"fn foo( {}".into(),
+		&Default::default(),
+	)
+	.unwrap_err();
+
+	assert!(matches!(err, Error::Parse { span: Some(s), .. } if s.line == 1), "{:?}", err);
+}
+
+#[test]
+fn serialize_works() {
+	let err = Error::MissingRange { component: "n".into(), extrinsic: "pallet::ext".into() };
+
+	assert_eq!(
+		serde_json::to_string(&err).unwrap(),
+		r#"{"kind":"missing-range","component":"n","extrinsic":"pallet::ext"}"#
+	);
+}
@@ -0,0 +1,299 @@
+use fancy_regex::Regex;
+use rstest::*;
+use std::path::PathBuf;
+
+use crate::{
+	lint::{
+		lint_files, Allow, Finding,
+		Lint::{self, *},
+		LintParams, Rule, Severity,
+	},
+	parse::pallet::parse_content,
+};
+
+/// A weight function with the given doc comments, parameters and body.
+fn weight_file(docs: &str, params: &str, body: &str) -> String {
+	format!(
+		"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {{\n{}\nfn ext({}) -> \
+		 Weight {{ {} }} }}",
+		docs, params, body
+	)
+}
+
+fn comp(name: &str) -> String {
+	name.into()
+}
+
+#[rstest]
+#[case("s: u32, ", "Weight::from_parts(5, 0).saturating_add(Weight::from_parts(1, 0).saturating_mul(s.into()))", vec![])]
+#[case("_s: u32, ", "Weight::from_parts(5, 0)", vec![])]
+#[case("s: u32, ", "Weight::from_parts(5, 0)", vec![UnusedComponent { component: comp("s") }])]
+#[case(
+	"",
+	"Weight::from_parts(5, 0).saturating_add(Weight::from_parts(1, 0).saturating_mul(s.into()))",
+	vec![UndeclaredComponent { component: comp("s") }]
+)]
+#[case(
+	"s: u32, ",
+	"Weight::from_parts(5, 0).saturating_add(T::DbWeight::get().reads(1)).saturating_add(WEIGHT_REF_TIME_PER_NANOS)",
+	vec![UnusedComponent { component: comp("s") }]
+)]
+fn lint_components_works(#[case] params: &str, #[case] body: &str, #[case] want: Vec<Lint>) {
+	let docs = "/// The range of component `s` is `[0, 100]`.";
+	let got =
+		parse_content("".into(), weight_file(docs, params, body), &Default::default()).unwrap();
+
+	assert_eq!(got[0].lints, want);
+}
+
+#[test]
+fn lint_missing_range_works() {
+	let body = "Weight::from_parts(5, 0).saturating_add(Weight::from_parts(1, \
+	            0).saturating_mul(s.into()).saturating_mul(n.into()))";
+	let docs = "/// The range of component `n` is `[0, 100]`.";
+	let got =
+		parse_content("".into(), weight_file(docs, "s: u32, n: u32, ", body), &Default::default())
+			.unwrap();
+
+	assert_eq!(got[0].lints, vec![MissingRange { component: comp("s") }]);
+}
+
+#[rstest]
+#[case("fn ext(s: u32, ) -> Weight;", vec![])]
+#[case("fn ext(_s: u32, ) -> Weight;", vec![])]
+#[case("fn other() -> Weight;", vec![])]
+#[case(
+	"fn ext(s: u32, n: u32, ) -> Weight;",
+	vec![SignatureMismatch { declared: vec![comp("s"), comp("n")], found: vec![comp("s")] }]
+)]
+fn lint_signature_mismatch_works(#[case] declaration: &str, #[case] want: Vec<Lint>) {
+	let body = "Weight::from_parts(5, 0).saturating_add(Weight::from_parts(1, \
+	            0).saturating_mul(s.into()))";
+	let docs = "/// The range of component `s` is `[0, 100]`.";
+	let content = format!(
+		"pub trait WeightInfo {{ {} }} {}",
+		declaration,
+		weight_file(docs, "s: u32, ", body)
+	);
+	let got = parse_content("".into(), content, &Default::default()).unwrap();
+
+	assert_eq!(got[0].lints, want);
+}
+
+#[test]
+fn lint_display_works() {
+	let lint = SignatureMismatch { declared: vec![comp("s"), comp("n")], found: vec![comp("s")] };
+
+	assert_eq!(lint.name(), "signature-mismatch");
+	assert_eq!(lint.to_string(), "Parameters (s) differ from the trait declaration (s, n)");
+	assert_eq!(
+		UnusedComponent { component: comp("s") }.to_string(),
+		"Component 's' is not used in the weight term"
+	);
+}
+
+/// Writes the weight functions into a pallet weight file in a temporary directory.
+fn pallet_file(dir: &str, name: &str, functions: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	let path = dir.join(name);
+	std::fs::write(
+		&path,
+		format!(
+			"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {{\n{}\n}}\n",
+			functions
+		),
+	)
+	.unwrap();
+	path
+}
+
+fn rules(findings: &[Finding]) -> Vec<(Rule, Option<&str>)> {
+	findings.iter().map(|f| (f.rule, f.extrinsic.as_deref())).collect()
+}
+
+#[test]
+fn lint_files_works() {
+	let file = pallet_file(
+		"lint_files_works",
+		"pallet_foo.rs",
+		"	fn zero() -> Weight {\n		Weight::from_parts(0, 0)\n	}\n	fn placeholder() -> Weight \
+		 {\n		Weight::zero()\n	}\n	fn old() -> Weight {\n		Weight::from_ref_time(5)\n	}\n	\
+		 /// The range of component `n` is `[7, 7]`.\n	fn constant(n: u32, ) -> Weight \
+		 {\n		Weight::from_parts(5, \
+		 1).saturating_add(Weight::from_parts(20_000_000_000, 0).saturating_mul(n.into()))\n	}\n	fn \
+		 storage() -> Weight {\n		Weight::from_parts(5, \
+		 1).saturating_add(T::DbWeight::get().reads(1001_u64))\n	}",
+	);
+	let findings = lint_files(&[file.clone()], &Default::default(), &Default::default()).unwrap();
+
+	assert_eq!(
+		rules(&findings),
+		vec![
+			(Rule::ZeroWeight, Some("zero")),
+			(Rule::ZeroPlaceholder, Some("placeholder")),
+			(Rule::MissingProof, Some("old")),
+			(Rule::ConstantRange, Some("constant")),
+			(Rule::HighSlope, Some("constant")),
+			(Rule::ExcessiveStorage, Some("storage")),
+		]
+	);
+	assert_eq!(findings[0].line, Some(2));
+	assert_eq!(findings[0].severity, Severity::Error);
+	assert_eq!(findings[0].file, file.display().to_string());
+	assert_eq!(
+		findings[4].message,
+		"Component 'n' adds 20.00ms per unit which is more than 10.00ms"
+	);
+}
+
+/// The line is the one of the selected impl and not of another impl or the trait declaration.
+#[test]
+fn lint_files_line_of_selected_impl() {
+	let dir = std::env::temp_dir().join("lint_files_line_of_selected_impl");
+	std::fs::create_dir_all(&dir).unwrap();
+	let file = dir.join("pallet_foo.rs");
+	let function = "	fn foo() -> Weight {\n		Weight::from_parts(0, 0)\n	}";
+	std::fs::write(
+		&file,
+		format!(
+			"pub trait WeightInfo {{\n	fn foo(\n	) -> Weight;\n}}\nimpl WeightInfo for () \
+			 {{\n{}\n}}\nimpl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {{\n{}\n}}\n",
+			function, function
+		),
+	)
+	.unwrap();
+
+	let findings = lint_files(&[file], &Default::default(), &Default::default()).unwrap();
+	assert_eq!(rules(&findings), vec![(Rule::ZeroWeight, Some("foo"))]);
+	assert_eq!(findings[0].line, Some(11));
+}
+
+#[test]
+fn lint_files_duplicates_and_errors() {
+	let function = "	fn foo() -> Weight {\n		Weight::from_parts(5, 1)\n	}";
+	let first = pallet_file("lint_files_duplicates_1", "pallet_foo.rs", function);
+	let second = pallet_file("lint_files_duplicates_2", "pallet_foo.rs", function);
+	let other = pallet_file("lint_files_duplicates_2", "pallet_bar.rs", function);
+	let missing = PathBuf::from("missing.rs");
+
+	// The same file with a different path.
+	let again = first.parent().unwrap().join(".").join("pallet_foo.rs");
+
+	// Same file names in different directories are not duplicates.
+	let files = [first.clone(), second, other, again.clone(), missing];
+	let findings = lint_files(&files, &Default::default(), &Default::default()).unwrap();
+
+	assert_eq!(
+		rules(&findings),
+		vec![(Rule::DuplicateExtrinsic, Some("foo")), (Rule::ParseError, None)]
+	);
+	assert_eq!(findings[0].file, again.display().to_string());
+	assert_eq!(findings[0].message, format!("Extrinsic 'foo' is also in {}", first.display()));
+}
+
+/// Distinct files of one directory are duplicates if they have the same pallet.
+#[test]
+fn lint_files_duplicates_across_files() {
+	let dir = std::env::temp_dir().join("lint_files_duplicates_across_files");
+	std::fs::create_dir_all(&dir).unwrap();
+	let file = |name: &str, pallet: &str| {
+		let path = dir.join(name);
+		std::fs::write(
+			&path,
+			format!(
+				"//! Autogenerated weights for {}\nimpl<T: frame_system::Config> WeightInfo for \
+				 SubstrateWeight<T> {{\n	fn foo() -> Weight {{\n		Weight::from_parts(5, \
+				 1)\n	}}\n}}\n",
+				pallet
+			),
+		)
+		.unwrap();
+		path
+	};
+	let first = file("pallet_foo.rs", "pallet_foo");
+	let stale = file("pallet_foo_old.rs", "pallet_foo");
+	let other = file("pallet_bar.rs", "`pallet_bar`");
+	// Without a header the pallet is the file name without extensions.
+	let copy = pallet_file(
+		"lint_files_duplicates_across_files",
+		"pallet_bar.rs.txt",
+		"	fn foo() -> Weight {\n		Weight::from_parts(5, 1)\n	}",
+	);
+
+	let files = [first.clone(), stale.clone(), other.clone(), copy.clone()];
+	let findings = lint_files(&files, &Default::default(), &Default::default()).unwrap();
+
+	let got = findings.iter().map(|f| (f.file.clone(), f.message.clone())).collect::<Vec<_>>();
+	assert_eq!(
+		got,
+		vec![
+			(copy.display().to_string(), format!("Extrinsic 'foo' is also in {}", other.display())),
+			(
+				stale.display().to_string(),
+				format!("Extrinsic 'foo' is also in {}", first.display())
+			),
+		]
+	);
+}
+
+#[rstest]
+#[case(vec![], 2)]
+#[case(vec!["zero-weight"], 1)]
+#[case(vec!["zero-weight:pallet_foo.rs::zero"], 1)]
+#[case(vec!["zero-weight:pallet_bar.rs::zero"], 2)]
+#[case(vec!["zero-weight:.*", "missing-proof"], 0)]
+fn lint_files_allow_works(#[case] allow: Vec<&str>, #[case] want: usize) {
+	let file = pallet_file(
+		"lint_files_allow_works",
+		"pallet_foo.rs",
+		"	fn zero() -> Weight {\n		Weight::from_parts(0, 0)\n	}\n	fn old() -> Weight \
+		 {\n		Weight::from_ref_time(5)\n	}",
+	);
+	let params = LintParams {
+		allow: allow.into_iter().map(|a| a.parse().unwrap()).collect(),
+		..Default::default()
+	};
+
+	assert_eq!(lint_files(&[file], &Default::default(), &params).unwrap().len(), want);
+}
+
+#[test]
+fn lint_files_allow_file_works() {
+	let file = pallet_file(
+		"lint_files_allow_file_works",
+		"pallet_foo.rs",
+		"	fn zero() -> Weight {\n		Weight::from_parts(0, 0)\n	}",
+	);
+	let allow_file = file.with_file_name("allow.txt");
+	std::fs::write(&allow_file, "# Known placeholders\n\nzero-weight:.*::zero\n").unwrap();
+	let params = LintParams { allow_file: Some(allow_file), ..Default::default() };
+
+	assert!(lint_files(&[file], &Default::default(), &params).unwrap().is_empty());
+}
+
+#[rstest]
+#[case("zero-weight", Ok(Allow { rule: Rule::ZeroWeight, extrinsic: None }))]
+#[case("Missing-Range:.*::bond", Ok(Allow { rule: Rule::MissingRange, extrinsic: Some(Regex::new(".*::bond").unwrap()) }))]
+#[case("frobnicate", Err("Unknown rule: frobnicate".to_string()))]
+fn allow_from_str_works(#[case] input: &str, #[case] want: Result<Allow, String>) {
+	assert_eq!(input.parse::<Allow>(), want);
+}
+
+/// The regex is validated when parsing and not only when a finding is checked.
+#[test]
+fn allow_from_str_invalid_regex_errors() {
+	let err = "zero-weight:(".parse::<Allow>().unwrap_err();
+	assert!(err.starts_with("Invalid allow regex: "), "{}", err);
+}
+
+#[test]
+fn rule_ids_are_kebab_case() {
+	let ids = Rule::all().iter().map(Rule::id).collect::<Vec<_>>();
+
+	assert_eq!(ids.len(), 12);
+	assert!(ids.contains(&"duplicate-extrinsic".to_string()));
+	assert!(ids.iter().all(|id| id.chars().all(|c| c.is_ascii_lowercase() || c == '-')));
+	// The Serialize and the clap names agree.
+	assert_eq!(serde_json::to_string(&Rule::HighSlope).unwrap(), "\"high-slope\"");
+}
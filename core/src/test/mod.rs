@@ -1,3 +1,28 @@
 pub mod core;
+pub mod error;
+pub mod lint;
 pub mod parse;
+pub mod rename;
+pub mod runtime;
 pub mod term;
+pub mod worst;
+
+use crate::parse::pallet::GenericExtrinsic;
+
+/// An extrinsic of a pallet weight file without any annotations.
+///
+/// Use it with `..` to set only the fields that a test needs.
+pub fn extrinsic<T>(pallet: &str, name: &str, term: T) -> GenericExtrinsic<T> {
+	GenericExtrinsic {
+		name: name.into(),
+		pallet: pallet.into(),
+		term,
+		comp_ranges: None,
+		storage: Vec::new(),
+		summary: Default::default(),
+		metadata: None,
+		kind: Default::default(),
+		lints: Vec::new(),
+		line: None,
+	}
+}
@@ -102,9 +102,9 @@ macro_rules! integration_test {
 					let path = std::fs::canonicalize(f).unwrap();
 					let f = &path;
 
-					match $crate::parse::try_parse_file(Path::new("."), f){
-						None => if pallet_files.contains(f) {
-							let err = $crate::parse::pallet::parse_file(f).unwrap_err();
+					match $crate::parse::try_parse_file(Path::new("."), f, &Default::default()){
+						Err(_) => if pallet_files.contains(f) {
+							let err = $crate::parse::pallet::parse_pallet_file(f, &Default::default()).unwrap_err();
 							assert!(false, "File {:?} could not be parsed as pallet: {:?}", f, err)
 						} else if overhead_files.contains(f) {
 							let err = $crate::parse::overhead::parse_file(f).unwrap_err();
@@ -113,13 +113,13 @@ macro_rules! integration_test {
 							let err = $crate::parse::storage::parse_file(f).unwrap_err();
 							assert!(false, "File {:?} could not be parsed as storage: {:?}", f, err)
 						},
-						Some(ParsedFile::Pallet(_)) => if !pallet_files.contains(f) {
+						Ok(ParsedFile::Pallet(_)) => if !pallet_files.contains(f) {
 							assert!(false, "File {:?} was parsed as pallet, but it was not expected to be", f)
 						},
-						Some(ParsedFile::Overhead(_)) => if !overhead_files.contains(f) {
+						Ok(ParsedFile::Overhead(_)) => if !overhead_files.contains(f) {
 							assert!(false, "File {:?} was parsed as overhead, but it was not expected to be", f)
 						},
-						Some(ParsedFile::Storage(_)) => if !storage_files.contains(f) {
+						Ok(ParsedFile::Storage(_)) => if !storage_files.contains(f) {
 							assert!(false, "File {:?} was parsed as storage, but it was not expected to be", f)
 						},
 					}
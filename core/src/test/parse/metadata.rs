@@ -0,0 +1,81 @@
+use rstest::*;
+use std::path::PathBuf;
+
+use crate::parse::{
+	metadata::{parse_content, BenchmarkMetadata},
+	pallet::parse_pallet_file,
+	read_file,
+};
+
+#[test]
+fn parses_weight_file() {
+	let content = read_file(&PathBuf::from("../test_data/new/staking_chromatic.rs.txt")).unwrap();
+	let got = parse_content(&content).unwrap();
+
+	assert_eq!(got.pallet.as_deref(), Some("pallet_staking"));
+	assert_eq!(got.date.as_deref(), Some("2023-01-25"));
+	assert_eq!(got.steps, Some(50));
+	assert_eq!(got.repeat, Some(20));
+	assert_eq!(got.worst_case_map_size, Some(1_000_000));
+	assert_eq!(got.hostname.as_deref(), Some("bm2"));
+	assert_eq!(got.cpu.as_deref(), Some("Intel(R) Core(TM) i7-7700K CPU @ 4.20GHz"));
+	assert_eq!(got.chain.as_deref(), Some("Some(\"dev\")"));
+	assert_eq!(got.command.first().map(String::as_str), Some("./target/production/substrate"));
+	assert_eq!(
+		got.command.last().map(String::as_str),
+		Some("--template=./.maintain/frame-weight-template.hbs")
+	);
+}
+
+#[test]
+fn parses_old_weight_file() {
+	let content = read_file(&PathBuf::from("../test_data/old/pallet_staking.rs.txt")).unwrap();
+	let got = parse_content(&content).unwrap();
+
+	assert_eq!(got.pallet.as_deref(), Some("pallet_staking"));
+	assert_eq!(got.steps, Some(50));
+	assert_eq!(got.repeat, Some(20));
+	assert_eq!(got.cpu, None);
+	assert_eq!(got.hostname, None);
+}
+
+#[test]
+fn parse_file_attaches_metadata() {
+	let got = parse_pallet_file(
+		&PathBuf::from("../test_data/new/staking_chromatic.rs.txt"),
+		&Default::default(),
+	)
+	.unwrap();
+
+	assert!(got
+		.iter()
+		.all(|e| e.metadata.as_ref().and_then(|m| m.hostname.as_deref()) == Some("bm2")));
+}
+
+#[rstest]
+#[case("impl WeightInfo for () {}", None)]
+#[case("//! STEPS: `50`, REPEAT: `20`", Some(BenchmarkMetadata { steps: Some(50), repeat: Some(20), ..Default::default() }))]
+#[case("//! HOSTNAME: `runner-1`, CPU: `AMD EPYC 7B13`", Some(BenchmarkMetadata { hostname: Some("runner-1".into()), cpu: Some("AMD EPYC 7B13".into()), ..Default::default() }))]
+fn parse_content_works(#[case] input: &str, #[case] want: Option<BenchmarkMetadata>) {
+	assert_eq!(parse_content(input), want);
+}
+
+#[rstest]
+#[case(BenchmarkMetadata::default(), vec![])]
+#[case(BenchmarkMetadata { steps: Some(50), ..Default::default() }, vec![])]
+#[case(BenchmarkMetadata { steps: Some(20), ..Default::default() }, vec!["Fewer steps: 50 vs 20"])]
+#[case(BenchmarkMetadata { repeat: Some(2), ..Default::default() }, vec!["Fewer repeats: 20 vs 2"])]
+#[case(BenchmarkMetadata { cpu: Some("B".into()), ..Default::default() }, vec!["Different CPU: `A` vs `B`"])]
+#[case(BenchmarkMetadata { hostname: Some("h2".into()), worst_case_map_size: Some(1), ..Default::default() }, vec!["Different hostname: `h1` vs `h2`", "Different worst case map size: `1000000` vs `1`"])]
+fn compare_works(#[case] new: BenchmarkMetadata, #[case] want: Vec<&str>) {
+	let old = BenchmarkMetadata {
+		steps: Some(50),
+		repeat: Some(20),
+		worst_case_map_size: Some(1_000_000),
+		hostname: Some("h1".into()),
+		cpu: Some("A".into()),
+		..Default::default()
+	};
+
+	assert_eq!(old.compare(&new), want);
+}
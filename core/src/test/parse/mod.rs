@@ -1,5 +1,6 @@
 pub mod helper;
 mod integration;
+mod metadata;
 mod overhead;
 mod pallet;
 mod storage;
@@ -7,7 +8,14 @@ mod storage;
 use rstest::*;
 use std::path::Path;
 
-use crate::parse::{PathStripping, PathStripping::*};
+use crate::{
+	parse::{
+		pallet::WeightKind, parse_file, report_file, try_parse_file, PathStripping,
+		PathStripping::*,
+	},
+	scope::SimpleScope,
+	Dimension, Error,
+};
 
 #[rstest]
 #[case("repo/pallet.rs", ".", FileName, "pallet.rs")]
@@ -47,3 +55,106 @@ fn fancy_regex_works() {
 		assert!(regex.is_match(input).unwrap());
 	}
 }
+
+/// Storage and overhead weight files are parsed into extrinsics with the constants substituted.
+#[rstest]
+#[case("../test_data/new/rocksdb_weights.rs.txt", &[("read", 25_000), ("write", 100_000)])]
+#[case("../test_data/new/paritydb_weights.rs.txt", &[("read", 8), ("write", 50)])]
+#[case("../test_data/new/extrinsic_weights.rs.txt", &[("ExtrinsicBaseWeight", 85_212_000)])]
+#[case("../test_data/chromatic/block_weights.rs.txt", &[("BlockExecutionWeight", 381_015_000)])]
+fn parse_file_any_kind_works(#[case] file: String, #[case] want: &[(&str, u128)]) {
+	let parsed = parse_file(Path::new(&file), &Default::default()).unwrap();
+
+	let got = parsed
+		.iter()
+		.map(|e| {
+			let time = e.term.simplify(Dimension::Time).unwrap();
+			(e.name.as_str(), time.eval(&SimpleScope::empty()).unwrap())
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(got, want);
+	let name = Path::new(&file).file_name().unwrap().to_str().unwrap();
+	assert!(parsed.iter().all(|e| e.pallet == name));
+}
+
+#[test]
+fn parse_file_unknown_kind_errors() {
+	let err = parse_file(
+		Path::new("../test_data/new/pallet_staking.rs.txt.missing"),
+		&Default::default(),
+	)
+	.unwrap_err();
+	assert!(matches!(err, crate::Error::Io { .. }));
+
+	let file = std::env::temp_dir().join("subweight_unknown_kind.rs");
+	std::fs::write(&file, "fn main() {}").unwrap();
+	let err = parse_file(&file, &Default::default()).unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		format!(
+			"{}: Unknown kind of weight file; pallet: Could not find a weight implementation in \
+			 the passed file; storage: No DB weights found; overhead: No Overhead weights found",
+			file.display()
+		)
+	);
+}
+
+/// Each parser gives its own reason for rejecting a file.
+#[test]
+fn try_parse_file_reports_rejections() {
+	let file = std::env::temp_dir().join("subweight_rejections.rs");
+	std::fs::write(&file, "parameter_types! { pub const Other: u64 = 5; }").unwrap();
+
+	let err = try_parse_file(Path::new("."), &file, &Default::default()).unwrap_err();
+	let Error::UnknownFile { file: got_file, rejections } = err else { panic!("{:?}", err) };
+	assert_eq!(got_file, file.display().to_string());
+	let got = rejections.iter().map(|r| (r.parser, r.error.message())).collect::<Vec<_>>();
+	assert_eq!(
+		got,
+		vec![
+			(
+				WeightKind::Pallet,
+				"Could not find a weight implementation in the passed file".into()
+			),
+			(WeightKind::Storage, "No DB weights found".into()),
+			(WeightKind::Overhead, "No Overhead weights found".into()),
+		]
+	);
+	assert!(rejections.iter().all(|r| r.error.location().is_some()));
+}
+
+#[rstest]
+#[case("../test_data/new/pallet_staking.rs.txt", Some(WeightKind::Pallet), 30)]
+#[case("../test_data/new/rocksdb_weights.rs.txt", Some(WeightKind::Storage), 2)]
+#[case("../test_data/chromatic/block_weights.rs.txt", Some(WeightKind::Overhead), 1)]
+#[case("../test_data/new/missing.rs.txt", None, 0)]
+fn report_file_works(
+	#[case] file: &str,
+	#[case] kind: Option<WeightKind>,
+	#[case] extrinsics: usize,
+) {
+	let (report, parsed) = report_file(Path::new(file), &Default::default());
+
+	assert_eq!(report.file, file);
+	assert_eq!((report.kind, report.extrinsics), (kind, extrinsics));
+	assert_eq!(report.error.is_some(), kind.is_none());
+	assert!(report.warnings.is_empty());
+	assert_eq!(parsed.len(), extrinsics);
+	assert!(parsed.iter().all(|e| file.ends_with(&e.pallet)));
+}
+
+#[test]
+fn report_file_warns() {
+	let file = std::env::temp_dir().join("subweight_report_warns.rs");
+	std::fs::write(
+		&file,
+		"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {\n\tfn foo() -> Weight \
+		 {\n\t\tWeight::from_ref_time(5 as u64)\n\t\t\t.saturating_add(T::DbWeight::get().\
+		 reads(1001_u64))\n\t}\n}\n",
+	)
+	.unwrap();
+
+	let (report, _) = report_file(&file, &Default::default());
+	assert_eq!(report.kind, Some(WeightKind::Pallet));
+	assert_eq!(report.warnings, vec!["foo: Call has 1001 READs".to_string()]);
+}
@@ -3,14 +3,17 @@ use std::{collections::HashMap, path::PathBuf};
 use syn::*;
 
 use crate::{
-	add, creads, cwrites, mul,
+	add, creads, cwrites,
+	lint::Lint,
+	mul,
 	parse::pallet::{
-		parse_content, parse_expression, parse_file, parse_scalar_expression, ChromaticExtrinsic,
-		ComponentRange,
+		parse_content, parse_expression, parse_pallet_file, parse_scalar_expression,
+		BenchmarkSummary, ChromaticExtrinsic, ComponentRange, ImplParams, StorageAccess,
 	},
 	reads, scalar,
 	scope::{Scope, *},
 	term::{ChromaticTerm, SimpleTerm, Term},
+	test::extrinsic,
 	traits::Weight,
 	val, var, writes,
 };
@@ -22,11 +25,48 @@ use crate::{
 #[case("../test_data/new/staking_chromatic.rs.txt")]
 #[case("../test_data/new/staking_chromatic.rs.txt")]
 fn parses_weight_files(#[case] path: PathBuf) {
-	if let Err(err) = parse_file(&path) {
+	if let Err(err) = parse_pallet_file(&path, &Default::default()) {
 		panic!("Failed to parse file: {:?} with error: {:?}", path, err);
 	}
 }
 
+/// A file with multiple impls and the time weight of each one.
+const MULTI_IMPL: &str = "impl WeightInfo for () { \
+	fn ext() -> Weight { 1 as Weight } \
+} \
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> { \
+	fn ext() -> Weight { 2 as Weight } \
+} \
+impl<T: frame_system::Config> pallet_xyz::WeightInfo for crate::MyChainWeight<T> { \
+	fn ext() -> Weight { 3 as Weight } \
+} \
+impl<T: frame_system::Config> Other for Helper<T> { \
+	fn ext() -> Weight { 4 as Weight } \
+}";
+
+/// Picks the impl whose name comes first in the list and not the one that comes first in the file.
+#[rstest]
+#[case(ImplParams::default().impl_names, Some(2))]
+#[case(vec!["()"], Some(1))]
+#[case(vec!["MyChainWeight"], Some(3))]
+#[case(vec!["crate::MyChainWeight", "SubstrateWeight"], Some(3))]
+#[case(vec!["Missing", "SubstrateWeight", "()"], Some(2))]
+#[case(vec!["*"], Some(1))]
+#[case(vec!["Helper"], Some(4))]
+#[case(vec!["Missing"], None)]
+fn parse_content_selects_impl<S: Into<String>>(
+	#[case] impl_names: Vec<S>,
+	#[case] want: Option<u128>,
+) {
+	let impls = ImplParams { impl_names: impl_names.into_iter().map(Into::into).collect() };
+	let got = parse_content("".into(), MULTI_IMPL.into(), &impls);
+
+	match want {
+		Some(want) => assert_eq!(got.unwrap()[0].term, Term::Value((want, 0).into())),
+		None => assert!(got.is_err()),
+	}
+}
+
 #[rstest]
 #[case(
 	"impl WeightInfo for () { \
@@ -43,13 +83,12 @@ fn parses_weight_files(#[case] path: PathBuf) {
 }"
 )]
 fn parse_function_v1_works(#[case] input: String) {
-	let got = parse_content("".into(), input).unwrap();
+	let got = parse_content("".into(), input, &Default::default()).unwrap();
 
 	let want = vec![ChromaticExtrinsic {
-		name: "ext".into(),
-		pallet: "".into(),
-		term: Term::Value((5, 0).into()),
-		comp_ranges: None,
+		lints: vec![Lint::MissingProof],
+		line: Some(1),
+		..extrinsic("", "ext", Term::Value((5, 0).into()))
 	}];
 	assert_eq!(want, got);
 }
@@ -62,7 +101,8 @@ fn parse_function_v1_works(#[case] input: String) {
 	}
 }",
 	5,
-	0
+	0,
+	2
 )]
 #[case(
 	"impl WeightInfo for () {
@@ -71,7 +111,8 @@ fn parse_function_v1_works(#[case] input: String) {
 	}
 }",
 	0,
-	5
+	5,
+	2
 )]
 #[case(
 	"impl<T: frame_system::Config> my_pallet::WeightInfo for WeightInfo<T> {
@@ -80,7 +121,8 @@ fn parse_function_v1_works(#[case] input: String) {
 	}
 }",
 	5,
-	0
+	0,
+	2
 )]
 #[case(
 	"impl<T: frame_system::Config> my_pallet::WeightInfo for WeightInfo<T> {
@@ -95,16 +137,26 @@ fn parse_function_v1_works(#[case] input: String) {
 			Weight::from_parts(15, 30)
 		}
 }",
-	15, 30
+	15, 30, 6
 )]
-fn parse_chromatic_function_works(#[case] input: String, #[case] t: u64, #[case] p: u64) {
-	let got = parse_content("".into(), input).unwrap();
+fn parse_chromatic_function_works(
+	#[case] input: String,
+	#[case] t: u64,
+	#[case] p: u64,
+	#[case] line: usize,
+) {
+	let mut got = parse_content("".into(), input, &Default::default()).unwrap();
+	// Comments are checked in `parse_storage_access_works` and `parse_summary_works`, lints in
+	// `test::lint`.
+	got.iter_mut().for_each(|ext| {
+		ext.storage.clear();
+		ext.summary = Default::default();
+		ext.lints.clear();
+	});
 
 	let want = vec![ChromaticExtrinsic {
-		name: "ext".into(),
-		pallet: "".into(),
-		term: Term::Value((t as u128, p as u128).into()),
-		comp_ranges: None,
+		line: Some(line),
+		..extrinsic("", "ext", Term::Value((t as u128, p as u128).into()))
 	}];
 	assert_eq!(want, got);
 }
@@ -123,21 +175,181 @@ fn parse_chromatic_function_works(#[case] input: String, #[case] t: u64, #[case]
 	}"#
 )]
 fn parse_component_range_works(#[case] input: String) {
-	let got = parse_content("".into(), input).unwrap();
+	let got = parse_content("".into(), input, &Default::default()).unwrap();
 
 	let ranges = HashMap::from([
 		("c".into(), ComponentRange { min: 1_337, max: 2000 }),
 		("d".into(), ComponentRange { min: 42, max: 999_999 }),
 	]);
 	let want = vec![ChromaticExtrinsic {
-		name: "ext".into(),
-		pallet: "".into(),
-		term: Term::Value((5, 0).into()),
 		comp_ranges: Some(ranges),
+		lints: vec![
+			Lint::MissingProof,
+			Lint::UnusedComponent { component: "c".into() },
+			Lint::UnusedComponent { component: "d".into() },
+		],
+		line: Some(4),
+		..extrinsic("", "ext", Term::Value((5, 0).into()))
 	}];
 	assert_eq!(want, got);
 }
 
+#[rstest]
+#[case(
+	r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		/// Storage: Staking Ledger (r:1 w:1)
+		/// Proof: Staking Ledger (max_values: None, max_size: Some(1091), added: 3566, mode: MaxEncodedLen)
+		/// Storage: Staking CurrentEra (r:1 w:0)
+		/// Proof Skipped: Staking CurrentEra (max_values: Some(1), max_size: None, mode: Measured)
+		/// Storage: unknown [0x3a7472616e73616374696f6e5f6c6576656c3a] (r:1 w:1)
+		fn ext() -> Weight {
+			Weight::from_parts(5, 0)
+		}
+	}"#
+)]
+#[case(
+	r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		/// Storage: `Staking::Ledger` (r:1 w:1)
+		/// Proof: `Staking::Ledger` (`max_values`: None, `max_size`: Some(1091), added: 3566, mode: `MaxEncodedLen`)
+		/// Storage: `Staking::CurrentEra` (r:1 w:0)
+		/// Proof: `Staking::CurrentEra` (`max_values`: Some(1), `max_size`: None, mode: `Measured`)
+		/// Storage: UNKNOWN KEY `0x3a7472616e73616374696f6e5f6c6576656c3a` (r:1 w:1)
+		/// Proof: UNKNOWN KEY `0x3a7472616e73616374696f6e5f6c6576656c3a` (r:1 w:1)
+		fn ext() -> Weight {
+			Weight::from_parts(5, 0)
+		}
+	}"#
+)]
+fn parse_storage_access_works(#[case] input: String) {
+	let got = parse_content("".into(), input, &Default::default()).unwrap();
+
+	let want = vec![
+		StorageAccess {
+			pallet: "Staking".into(),
+			item: "Ledger".into(),
+			reads: 1,
+			writes: 1,
+			max_size: Some(1091),
+			mode: Some("MaxEncodedLen".into()),
+		},
+		StorageAccess {
+			pallet: "Staking".into(),
+			item: "CurrentEra".into(),
+			reads: 1,
+			writes: 0,
+			max_size: None,
+			mode: Some("Measured".into()),
+		},
+		StorageAccess {
+			pallet: "".into(),
+			item: "0x3a7472616e73616374696f6e5f6c6576656c3a".into(),
+			reads: 1,
+			writes: 1,
+			max_size: None,
+			mode: None,
+		},
+	];
+	assert_eq!(got.len(), 1);
+	assert_eq!(got[0].storage, want);
+}
+
+#[test]
+fn parse_storage_access_file_works() {
+	let got = parse_pallet_file(
+		&PathBuf::from("../test_data/new/staking_chromatic.rs.txt"),
+		&Default::default(),
+	)
+	.unwrap();
+	let bond = got.iter().find(|e| e.name == "bond").unwrap();
+
+	assert_eq!(bond.storage.len(), 5);
+	assert_eq!(bond.storage.iter().map(|s| s.reads).sum::<u32>(), 4);
+	assert_eq!(bond.storage.iter().map(|s| s.writes).sum::<u32>(), 4);
+	assert_eq!(bond.storage[4].item, "Payee");
+	assert_eq!(bond.storage[4].max_size, Some(73));
+}
+
+#[rstest]
+#[case(
+	r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		fn ext() -> Weight {
+			// Proof Size summary in bytes:
+			//  Measured:  `1079`
+			//  Estimated: `10386`
+			// Minimum execution time: 40_015 nanoseconds.
+			Weight::from_parts(5, 0)
+		}
+	}"#,
+	BenchmarkSummary { measured_proof: Some(1079), estimated_proof: Some(10386), min_time: Some(40_015_000) }
+)]
+#[case(
+	r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		fn ext() -> Weight {
+			// Minimum execution time: 40_015_000 picoseconds.
+			Weight::from_parts(5, 0)
+		}
+	}"#,
+	BenchmarkSummary { min_time: Some(40_015_000), ..Default::default() }
+)]
+#[case(
+	r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		fn ext() -> Weight {
+			Weight::from_parts(5, 0)
+		}
+	}"#,
+	BenchmarkSummary::default()
+)]
+fn parse_summary_works(#[case] input: String, #[case] want: BenchmarkSummary) {
+	let got = parse_content("".into(), input, &Default::default()).unwrap();
+
+	assert_eq!(got.len(), 1);
+	assert_eq!(got[0].summary, want);
+}
+
+/// The summaries are taken from the selected impl and not from the first one in the file.
+#[rstest]
+#[case("SubstrateWeight", Some(1))]
+#[case("()", Some(2))]
+fn parse_summary_of_selected_impl(#[case] impl_name: &str, #[case] measured: Option<u128>) {
+	let input = r#"impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+		fn ext() -> Weight {
+			//  Measured:  `1`
+			Weight::from_parts(5, 0)
+		}
+	}
+	impl WeightInfo for () {
+		fn ext() -> Weight {
+			//  Measured:  `2`
+			Weight::from_parts(5, 0)
+		}
+	}"#;
+	let impls = ImplParams { impl_names: vec![impl_name.into()] };
+	let got = parse_content("".into(), input.into(), &impls).unwrap();
+
+	assert_eq!(got[0].summary.measured_proof, measured);
+}
+
+#[test]
+fn parse_summary_file_works() {
+	let got = parse_pallet_file(
+		&PathBuf::from("../test_data/new/staking_chromatic.rs.txt"),
+		&Default::default(),
+	)
+	.unwrap();
+	let bond = got.iter().find(|e| e.name == "bond").unwrap();
+	let unbond = got.iter().find(|e| e.name == "unbond").unwrap();
+
+	assert_eq!(
+		bond.summary,
+		BenchmarkSummary {
+			measured_proof: Some(1079),
+			estimated_proof: Some(10386),
+			min_time: Some(40_015_000)
+		}
+	);
+	assert_ne!(bond.summary, unbond.summary);
+}
+
 #[rstest]
 // Basic arithmetic.
 #[case("(123 as Weight)",
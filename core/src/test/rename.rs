@@ -0,0 +1,103 @@
+use rstest::*;
+
+use crate::{
+	parse::pallet::*,
+	rename::{detect_renames, name_similarity, RenameParams},
+	test::extrinsic,
+	*,
+};
+
+fn ext(pallet: &str, name: &str, time: u128, proof: u128) -> ChromaticExtrinsic {
+	extrinsic(pallet, name, cval!((time, proof).into()))
+}
+
+fn enabled() -> RenameParams {
+	RenameParams { detect_renames: true, ..Default::default() }
+}
+
+#[rstest]
+#[case("set_payee", "set_payee", 1.0)]
+#[case("set_payee", "setPayee", 1.0)]
+#[case("abc", "xyz", 0.0)]
+#[case("bond", "bond_extra", 8.0 / 13.0)]
+#[case("", "", 1.0)]
+fn name_similarity_works(#[case] a: &str, #[case] b: &str, #[case] want: f64) {
+	assert!((name_similarity(a, b) - want).abs() < 0.0001, "{} vs {}", a, b);
+}
+
+#[test]
+fn detect_renames_works() {
+	let olds = vec![
+		ext("staking", "kept", 1, 1),
+		ext("staking", "set_payee", 100, 10),
+		ext("staking", "chill", 500, 50),
+		ext("old_staking", "bond", 200, 20),
+	];
+	let news = vec![
+		ext("staking", "kept", 1, 1),
+		ext("staking", "update_payee", 100, 10),
+		ext("staking", "unrelated", 7, 0),
+		ext("staking", "bond", 300, 30),
+		ext("new_staking", "bond", 200, 20),
+	];
+
+	let got = detect_renames(&olds, &news, &enabled())
+		.into_iter()
+		.map(|r| (r.is_move(), r.old_file, r.old_name, r.new_file, r.new_name))
+		.collect::<Vec<_>>();
+	let s = |s: &str| s.to_string();
+	assert_eq!(
+		got,
+		vec![
+			(true, s("old_staking"), s("bond"), s("new_staking"), s("bond")),
+			(false, s("staking"), s("set_payee"), s("staking"), s("update_payee")),
+		]
+	);
+}
+
+#[test]
+fn detect_renames_disabled_by_default() {
+	let olds = vec![ext("staking", "set_payee", 100, 10)];
+	let news = vec![ext("staking", "update_payee", 100, 10)];
+
+	assert!(detect_renames(&olds, &news, &Default::default()).is_empty());
+	assert_eq!(detect_renames(&olds, &news, &enabled()).len(), 1);
+}
+
+#[rstest]
+#[case(0.75, true)]
+#[case(0.99, false)]
+fn detect_renames_threshold_works(#[case] rename_threshold: f64, #[case] paired: bool) {
+	let olds = vec![ext("staking", "set_payee", 100, 10)];
+	let news = vec![ext("staking", "update_payee", 110, 10)];
+	let params = RenameParams { rename_threshold, ..enabled() };
+
+	assert_eq!(detect_renames(&olds, &news, &params).len() == 1, paired);
+}
+
+#[rstest]
+#[case(None)]
+#[case(Some(DimensionSelector::Time))]
+fn compare_files_renames_works(#[case] chromatic: Option<DimensionSelector>) {
+	let olds = vec![ext("staking", "set_payee", 100, 10), ext("staking", "chill", 1, 1)];
+	let news = vec![ext("staking", "update_payee", 110, 10), ext("staking", "chill", 1, 1)];
+	let params = CompareParams { chromatic, renames: enabled(), ..Default::default() };
+
+	let diff = compare_files(olds, news, &params, &FilterParams::default()).unwrap();
+	assert_eq!(diff.len(), 2);
+	let renamed = diff.iter().find(|d| d.name == "update_payee").unwrap();
+	let term = renamed.term().unwrap();
+	assert_eq!(term.change, RelativeChange::Renamed);
+	assert_eq!((term.old_v, term.new_v), (Some(100), Some(110)));
+	assert!((term.percent - 10.0).abs() < 0.001);
+	assert_eq!(renamed.renamed_from.as_ref().unwrap().old_name, "set_payee");
+	if let Some(chromatic) = &renamed.chromatic {
+		assert_eq!(chromatic.proof.term().unwrap().change, RelativeChange::Renamed);
+	}
+
+	// Renames are only filtered out explicitly.
+	let filter = FilterParams { change: Some(vec![RelativeChange::Changed]), ..Default::default() };
+	assert!(filter_changes(diff.clone(), &filter).is_empty());
+	let offenders = FailOn::Changed(5.0).offenders(&diff);
+	assert_eq!(offenders.len(), 1);
+}
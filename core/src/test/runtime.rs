@@ -0,0 +1,94 @@
+use rstest::*;
+
+use crate::{parse::pallet::*, runtime::compare_runtimes, test::extrinsic, *};
+
+fn ext(path: &str, name: &str, time: u128) -> ChromaticExtrinsic {
+	extrinsic(path, name, cval!((time, 0).into()))
+}
+
+#[rstest]
+#[case("runtime/([^/]+)/")]
+#[case("runtime/(?P<runtime>[^/]+)/src")]
+#[case("(?<=runtime/)[ab]")]
+fn compare_runtimes_works(#[case] pattern: &str) {
+	let olds = vec![
+		ext("old/runtime/a/src/weights/pallet_x.rs", "transfer", 100),
+		ext("old/runtime/b/src/weights/pallet_x.rs", "transfer", 100),
+		ext("old/runtime/b/src/weights/pallet_y.rs", "vote", 10),
+		ext("old/pallets/pallet_z.rs", "skipped", 1),
+	];
+	let news = vec![
+		ext("new/runtime/a/src/weights/pallet_x.rs", "transfer", 150),
+		ext("new/runtime/b/src/weights/pallet_x.rs", "transfer", 100),
+		ext("new/runtime/b/src/weights/pallet_y.rs", "vote", 10),
+	];
+
+	let matrix =
+		compare_runtimes(olds, news, &CompareParams::default(), &FilterParams::default(), pattern)
+			.unwrap();
+	assert_eq!(matrix.runtimes, vec!["a", "b"]);
+
+	let rows = matrix
+		.rows
+		.iter()
+		.map(|row| {
+			let cells = row
+				.changes
+				.iter()
+				.map(|(runtime, diff)| (runtime.as_str(), diff.term().unwrap().change))
+				.collect::<Vec<_>>();
+			(row.file.as_str(), row.name.as_str(), cells)
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(
+		rows,
+		vec![
+			(
+				"pallet_x.rs",
+				"transfer",
+				vec![("a", RelativeChange::Changed), ("b", RelativeChange::Unchanged)]
+			),
+			("pallet_y.rs", "vote", vec![("b", RelativeChange::Unchanged)]),
+		]
+	);
+	assert_eq!(matrix.rows[0].changes["a"].term().unwrap().percent, 50.0);
+}
+
+/// A row is kept if any runtime passes the filter.
+#[test]
+fn runtime_matrix_filter_works() {
+	let olds = vec![
+		ext("runtime/a/x.rs", "transfer", 100),
+		ext("runtime/b/x.rs", "transfer", 100),
+		ext("runtime/a/x.rs", "vote", 100),
+		ext("runtime/b/x.rs", "vote", 100),
+	];
+	let news = vec![
+		ext("runtime/a/x.rs", "transfer", 150),
+		ext("runtime/b/x.rs", "transfer", 101),
+		ext("runtime/a/x.rs", "vote", 101),
+		ext("runtime/b/x.rs", "vote", 101),
+	];
+	let filter = FilterParams { threshold: 5.0, ..Default::default() };
+
+	let mut matrix =
+		compare_runtimes(olds, news, &CompareParams::default(), &filter, "runtime/(.)/")
+			.unwrap()
+			.filter(&filter);
+	matrix.sort();
+	assert_eq!(matrix.rows.len(), 1);
+	assert_eq!(matrix.rows[0].name, "transfer");
+	assert_eq!(matrix.rows[0].changes.len(), 2);
+
+	let files = matrix.diffs().into_iter().map(|d| d.file).collect::<Vec<_>>();
+	assert_eq!(files, vec!["a/x.rs", "b/x.rs"]);
+}
+
+#[test]
+fn compare_runtimes_invalid_pattern_errors() {
+	let err =
+		compare_runtimes(vec![], vec![], &CompareParams::default(), &FilterParams::default(), "(")
+			.err()
+			.unwrap();
+	assert!(matches!(err, Error::InvalidParam { .. }));
+}
@@ -1,7 +1,7 @@
 use rstest::*;
 use std::collections::BTreeSet as Set;
 
-use crate::{add, mul, scalar, scope::SimpleScope, term::SimpleTerm, var};
+use crate::{add, mul, scalar, scope::SimpleScope, term::SimpleTerm, val, var};
 
 #[rstest]
 #[case(scalar!(123), vec![], vec![])]
@@ -37,9 +37,9 @@ fn term_find_largest_factor_works(#[case] term: SimpleTerm, #[case] largest: Opt
 #[rstest]
 #[case(scalar!(123), "123")]
 #[case(var!("x"), "x")]
-#[case(add!(var!("x"), scalar!(123)), "x + 123")]
+#[case(add!(var!("x"), scalar!(123)), "123 + x")]
 #[case(add!(var!("x"), scalar!(0)), "x")]
-#[case(mul!(var!("x"), scalar!(123)), "x * 123")]
+#[case(mul!(var!("x"), scalar!(123)), "123 * x")]
 #[case(mul!(var!("x"), scalar!(1)), "x")]
 #[case(add!(add!(scalar!(0), scalar!(0)), scalar!(0)), "0")]
 #[case(add!(add!(scalar!(0), scalar!(0)), add!(scalar!(0), scalar!(0))), "0")]
@@ -50,13 +50,72 @@ fn term_find_largest_factor_works(#[case] term: SimpleTerm, #[case] largest: Opt
 )),
 "12.68M + 4.41M * c + READ + WRITE")]
 #[case(mul!(var!("m"), var!("READ")), "m * READ")]
+#[case(mul!(var!("READ"), var!("m")), "m * READ")]
+#[case(add!(mul!(var!("n"), scalar!(2)), mul!(scalar!(3), var!("n"))), "5 * n")]
+#[case(mul!(add!(scalar!(1), var!("n")), scalar!(2)), "2 + 2 * n")]
+#[case(add!(mul!(var!("b"), var!("a")), add!(var!("b"), var!("a"))), "a + b + a * b")]
 #[case(mul!(mul!(scalar!(1), var!("m")), var!("READ")), "m * READ")]
 #[case(add!(add!(add!(add!(scalar!(9909), mul!(scalar!(0), var!("l"))), mul!(scalar!(0), var!("s"))), mul!(scalar!(3), var!("READ"))), mul!(scalar!(3), var!("WRITE"))), "9.91K + 3 * READ + 3 * WRITE")]
 fn term_fmt_with_brackets_works(#[case] term: SimpleTerm, #[case] expected: &str) {
 	assert_eq!(format!("{}", term), expected);
 }
 
+#[rstest]
+#[case(add!(var!("a"), var!("b")), add!(var!("b"), var!("a")))]
+#[case(mul!(scalar!(2), add!(var!("a"), scalar!(1))), add!(scalar!(2), mul!(var!("a"), scalar!(2))))]
+#[case(add!(var!("a"), scalar!(0)), var!("a"))]
+#[case(mul!(var!("a"), var!("b")), mul!(var!("b"), var!("a")))]
+#[case(add!(var!("a"), var!("a")), mul!(scalar!(2), var!("a")))]
+#[case(var!("comp_a"), var!("compa"))]
+#[case(scalar!(5), val!(5))]
+fn term_normalized_eq_works(#[case] a: SimpleTerm, #[case] b: SimpleTerm) {
+	assert_eq!(a, b);
+	assert_eq!(a.normalize(), b.normalize());
+}
+
+#[rstest]
+#[case(var!("a"), var!("b"))]
+#[case(add!(var!("a"), scalar!(1)), var!("a"))]
+#[case(mul!(var!("a"), var!("a")), var!("a"))]
+#[case(mul!(scalar!(2), var!("a")), mul!(scalar!(3), var!("a")))]
+fn term_normalized_ne_works(#[case] a: SimpleTerm, #[case] b: SimpleTerm) {
+	assert_ne!(a, b);
+}
+
+#[test]
+fn term_normalize_keeps_value() {
+	let scope = SimpleScope::empty().with_var("a", scalar!(7)).with_var("b", scalar!(3));
+	let term = mul!(add!(var!("a"), mul!(scalar!(2), var!("b"))), add!(scalar!(10), var!("a")));
+
+	assert_eq!(term.normalize().eval(&scope).unwrap(), term.eval(&scope).unwrap());
+	assert_eq!(term.to_string(), "10 * a + 20 * b + a * a + 2 * a * b");
+}
+
+#[test]
+fn term_normalize_overflow_keeps_term() {
+	let term = mul!(scalar!(u128::MAX), add!(scalar!(2), var!("a")));
+
+	assert!(term.normalize().same_structure(&term));
+}
+
 /*#[case(scalar!(123), scalar!(123))]
 fn test_substitute_works(#[case] term: SimpleTern, #[case] expected: SimpleTerm) {
 	term
 }*/
+
+const MAX: u128 = u64::MAX as u128;
+
+#[rstest]
+#[case(add!(scalar!(1), scalar!(2)), 3, false)]
+#[case(add!(scalar!(MAX), scalar!(0)), MAX, false)]
+#[case(add!(scalar!(MAX), scalar!(1)), MAX, true)]
+#[case(mul!(scalar!(MAX), scalar!(2)), MAX, true)]
+#[case(mul!(scalar!(u128::MAX), scalar!(u128::MAX)), MAX, true)]
+#[case(add!(mul!(scalar!(MAX), scalar!(2)), scalar!(0)), MAX, true)]
+#[case(scalar!(MAX + 1), MAX, true)]
+fn term_eval_saturates(#[case] term: SimpleTerm, #[case] value: u128, #[case] saturated: bool) {
+	let scope = SimpleScope::empty();
+
+	assert_eq!(term.eval_saturating(&scope).unwrap(), (value, saturated));
+	assert_eq!(term.eval(&scope).unwrap(), value);
+}
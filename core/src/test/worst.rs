@@ -0,0 +1,202 @@
+use rstest::*;
+
+use crate::{
+	add, compare_extrinsics, compare_terms, component_bounds, mul,
+	parse::pallet::ComponentRange,
+	scalar,
+	scope::SimpleScope,
+	term::SimpleTerm,
+	test::extrinsic,
+	var,
+	worst::{find_worst, ComponentBounds, Objective},
+	CompareMethod, CompareParams, Error, SimpleExtrinsic,
+};
+
+/// All combinations of the lowest and highest value of each free component.
+///
+/// This is what [`find_worst`] searches through without enumerating it and serves as its brute
+/// force oracle.
+pub fn extend_scoped_components(
+	a: Option<&SimpleExtrinsic>,
+	b: Option<&SimpleExtrinsic>,
+	method: CompareMethod,
+	scope: &SimpleScope,
+) -> Result<Vec<SimpleScope>, Error> {
+	let bounds = component_bounds(a, b, method, scope)?;
+
+	let mut scopes = Vec::new();
+	for i in 0..(1u64 << bounds.len()) {
+		let mut scope = scope.clone();
+		for (c, (component, (lowest, highest))) in bounds.iter().enumerate() {
+			// The last component changes fastest, so the scopes are sorted by their values.
+			let value = if i & (1 << (bounds.len() - 1 - c)) == 0 { lowest } else { highest };
+			scope.put_var(component, SimpleTerm::Scalar(*value as u128));
+		}
+		if !scope.is_empty() && !scopes.contains(&scope) {
+			scopes.push(scope);
+		}
+	}
+	Ok(scopes)
+}
+
+fn bounds(components: &[(&str, u32, u32)]) -> ComponentBounds {
+	components.iter().map(|(c, lo, hi)| (c.to_string(), (*lo, *hi))).collect()
+}
+
+fn ranged(term: SimpleTerm, ranges: &[(&str, u32, u32)]) -> SimpleExtrinsic {
+	SimpleExtrinsic {
+		comp_ranges: Some(
+			ranges
+				.iter()
+				.map(|(c, min, max)| (c.to_string(), ComponentRange { min: *min, max: *max }))
+				.collect(),
+		),
+		..extrinsic("pallet", "ext", term)
+	}
+}
+
+fn params() -> CompareParams {
+	CompareParams { method: CompareMethod::ExactWorst, ..Default::default() }
+}
+
+fn value(scope: &SimpleScope, var: &str) -> u128 {
+	scope.get(var).and_then(|v| v.as_scalar()).unwrap()
+}
+
+#[rstest]
+// The ratio is largest where the base weight dominates.
+#[case(add!(scalar!(100), var!("n")), add!(scalar!(300), mul!(scalar!(2), var!("n"))), 0)]
+// The ratio is largest where the slope dominates.
+#[case(add!(scalar!(10), var!("n")), add!(scalar!(10), mul!(scalar!(2), var!("n"))), 100)]
+fn find_worst_relative_works(#[case] old: SimpleTerm, #[case] new: SimpleTerm, #[case] n: u128) {
+	let bounds = bounds(&[("n", 0, 100)]);
+	let scope = SimpleScope::empty();
+
+	let worst = find_worst(Some(&old), Some(&new), &bounds, &scope, Objective::Relative).unwrap();
+	assert_eq!(value(&worst, "n"), n);
+}
+
+#[test]
+fn find_worst_absolute_works() {
+	// `new - old = 100n - n^2` has its maximum between the bounds.
+	let old = mul!(var!("n"), var!("n"));
+	let new = mul!(scalar!(100), var!("n"));
+	let bounds = bounds(&[("n", 0, 100)]);
+
+	let worst =
+		find_worst(Some(&old), Some(&new), &bounds, &SimpleScope::empty(), Objective::Absolute)
+			.unwrap();
+	assert_eq!(value(&worst, "n"), 50);
+}
+
+#[test]
+fn find_worst_value_works() {
+	let term = add!(mul!(scalar!(3), var!("a")), var!("b"));
+	let bounds = bounds(&[("a", 1, 10), ("b", 5, 7)]);
+
+	let worst =
+		find_worst(None, Some(&term), &bounds, &SimpleScope::empty(), Objective::Value).unwrap();
+	assert_eq!((value(&worst, "a"), value(&worst, "b")), (10, 7));
+}
+
+#[test]
+fn find_worst_interacting_components_works() {
+	// The worst case depends on both components at once.
+	let old = add!(add!(scalar!(100), mul!(scalar!(10), var!("a"))), mul!(scalar!(10), var!("b")));
+	let new = add!(
+		add!(scalar!(100), mul!(scalar!(10), var!("a"))),
+		add!(mul!(scalar!(10), var!("b")), mul!(scalar!(5), mul!(var!("a"), var!("b"))))
+	);
+	let bounds = bounds(&[("a", 0, 10), ("b", 0, 10)]);
+
+	let worst =
+		find_worst(Some(&old), Some(&new), &bounds, &SimpleScope::empty(), Objective::Relative)
+			.unwrap();
+	assert_eq!((value(&worst, "a"), value(&worst, "b")), (10, 10));
+}
+
+/// The solver finds the same worst case as evaluating all corners.
+#[rstest]
+#[case(
+	add!(add!(scalar!(1_000), mul!(scalar!(7), var!("a"))), mul!(scalar!(3), var!("b"))),
+	add!(add!(scalar!(900), mul!(scalar!(9), var!("a"))), mul!(scalar!(2), var!("b")))
+)]
+#[case(
+	add!(scalar!(50), mul!(var!("a"), var!("b"))),
+	add!(add!(scalar!(60), mul!(scalar!(4), var!("a"))), mul!(var!("b"), var!("c")))
+)]
+#[case(
+	add!(add!(scalar!(10), var!("a")), add!(var!("b"), var!("c"))),
+	add!(add!(scalar!(5), mul!(scalar!(2), var!("a"))), add!(var!("b"), scalar!(3)))
+)]
+fn find_worst_matches_corners(#[case] old: SimpleTerm, #[case] new: SimpleTerm) {
+	let ranges = [("a", 1, 20), ("b", 0, 30), ("c", 2, 5)];
+	let (old, new) = (ranged(old, &ranges), ranged(new, &ranges));
+	let method = CompareMethod::ExactWorst;
+	let scope = SimpleScope::empty();
+
+	let corners = extend_scoped_components(Some(&old), Some(&new), method, &scope)
+		.unwrap()
+		.into_iter()
+		.map(|s| compare_terms(Some(&old.term), Some(&new.term), method, &s).unwrap().percent)
+		.fold(f64::MIN, f64::max);
+	let got = compare_extrinsics(Some(old), Some(new), &params()).unwrap();
+
+	assert!((got.percent - corners).abs() < 1e-9, "{} vs {}", got.percent, corners);
+}
+
+#[test]
+fn compare_extrinsics_many_components_works() {
+	let components = (0..40).map(|i| format!("c{}", i)).collect::<Vec<_>>();
+	let ranges = components.iter().map(|c| (c.as_str(), 0, 10)).collect::<Vec<_>>();
+	let sum = |factor: u128| {
+		components
+			.iter()
+			.fold(scalar!(100), |acc, c| add!(acc, mul!(scalar!(factor), var!(c.as_str()))))
+	};
+	let old = ranged(sum(1), &ranges);
+	let new = ranged(add!(sum(1), mul!(scalar!(2), var!("c7"))), &ranges);
+
+	let got = compare_extrinsics(Some(old), Some(new), &params()).unwrap();
+	// Only `c7` is maxed out since all others only add to the old weight.
+	assert_eq!(got.old_v, Some(110));
+	assert_eq!(got.new_v, Some(130));
+	assert_eq!(value(&got.scope, "c7"), 10);
+	assert!(components.iter().filter(|c| *c != "c7").all(|c| value(&got.scope, c) == 0));
+}
+
+#[test]
+fn compare_extrinsics_reports_absolute_worst() {
+	let ranges = [("n", 0, 100)];
+	let old = ranged(add!(scalar!(100), var!("n")), &ranges);
+	let new = ranged(add!(scalar!(300), mul!(scalar!(2), var!("n"))), &ranges);
+
+	let got = compare_extrinsics(Some(old), Some(new), &params()).unwrap();
+	assert_eq!(value(&got.scope, "n"), 0);
+	assert_eq!((got.old_v, got.new_v), (Some(100), Some(300)));
+
+	let absolute = got.absolute.unwrap();
+	assert_eq!(value(&absolute.scope, "n"), 100);
+	assert_eq!((absolute.old_v, absolute.new_v, absolute.diff()), (200, 500, 300));
+}
+
+#[test]
+fn compare_extrinsics_base_has_no_absolute() {
+	let ranges = [("n", 0, 100)];
+	let old = ranged(add!(scalar!(100), var!("n")), &ranges);
+	let new = ranged(add!(scalar!(300), mul!(scalar!(2), var!("n"))), &ranges);
+	let params = CompareParams { method: CompareMethod::Base, ..params() };
+
+	let got = compare_extrinsics(Some(old), Some(new), &params).unwrap();
+	assert!(got.absolute.is_none());
+	assert_eq!(value(&got.scope, "n"), 0);
+}
+
+#[test]
+fn find_worst_missing_bounds_errors() {
+	let term = var!("n");
+
+	let err = find_worst(None, Some(&term), &bounds(&[]), &SimpleScope::empty(), Objective::Value)
+		.unwrap_err();
+	assert_eq!(err.to_string(), "Variable 'n' not found");
+}
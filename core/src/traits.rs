@@ -8,6 +8,21 @@ pub trait Zero {
 	fn zero() -> Self;
 }
 
+/// The largest value of a weight dimension.
+///
+/// Substrate stores `ref_time` and `proof_size` as `u64` and saturates at this value.
+pub const MAX_WEIGHT: u128 = u64::MAX as u128;
+
+/// Adds two weight values like the runtime does with `u64::saturating_add`.
+pub fn saturating_add(a: u128, b: u128) -> u128 {
+	a.saturating_add(b).min(MAX_WEIGHT)
+}
+
+/// Multiplies two weight values like the runtime does with `u64::saturating_mul`.
+pub fn saturating_mul(a: u128, b: u128) -> u128 {
+	a.saturating_mul(b).min(MAX_WEIGHT)
+}
+
 impl One for u128 {
 	fn one() -> Self {
 		1
@@ -26,6 +41,34 @@ impl ValueFormatter for u128 {
 	}
 }
 
+/// A value that can be the coefficient of a monomial in a normalized [`crate::term::Term`].
+///
+/// The operations return `None` when they overflow or make no sense for the type.
+pub trait Coefficient: Clone + PartialEq + Zero {
+	fn try_add(&self, other: &Self) -> Option<Self>;
+	fn try_mul(&self, other: &Self) -> Option<Self>;
+	fn try_scale(&self, scalar: u128) -> Option<Self>;
+	fn try_from_scalar(scalar: u128) -> Option<Self>;
+}
+
+impl Coefficient for u128 {
+	fn try_add(&self, other: &Self) -> Option<Self> {
+		self.checked_add(*other)
+	}
+
+	fn try_mul(&self, other: &Self) -> Option<Self> {
+		self.checked_mul(*other)
+	}
+
+	fn try_scale(&self, scalar: u128) -> Option<Self> {
+		self.checked_mul(scalar)
+	}
+
+	fn try_from_scalar(scalar: u128) -> Option<Self> {
+		Some(scalar)
+	}
+}
+
 pub trait ValueFormatter {
 	fn format_scalar(&self) -> String;
 }
@@ -66,6 +109,28 @@ impl From<u128> for Weight {
 	}
 }
 
+/// Weights can only be added and scaled; multiplying two of them is meaningless.
+impl Coefficient for Weight {
+	fn try_add(&self, other: &Self) -> Option<Self> {
+		Some(Self {
+			time: self.time.try_add(&other.time)?,
+			proof: self.proof.try_add(&other.proof)?,
+		})
+	}
+
+	fn try_mul(&self, _other: &Self) -> Option<Self> {
+		None
+	}
+
+	fn try_scale(&self, scalar: u128) -> Option<Self> {
+		Some(Self { time: self.time.try_scale(scalar)?, proof: self.proof.try_scale(scalar)? })
+	}
+
+	fn try_from_scalar(_scalar: u128) -> Option<Self> {
+		None
+	}
+}
+
 impl ValueFormatter for Weight {
 	fn format_scalar(&self) -> String {
 		format!("({}, {})", self.time, self.proof)
@@ -80,6 +145,13 @@ impl core::fmt::Display for Weight {
 
 impl Weight {
 	pub fn mul_scalar(&self, other: u128) -> Self {
-		Self { time: self.time * other, proof: self.proof * other }
+		Self { time: saturating_mul(self.time, other), proof: saturating_mul(self.proof, other) }
+	}
+
+	pub fn saturating_add(&self, other: &Self) -> Self {
+		Self {
+			time: saturating_add(self.time, other.time),
+			proof: saturating_add(self.proof, other.proof),
+		}
 	}
 }
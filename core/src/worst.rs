@@ -0,0 +1,246 @@
+//! Finds the component values at which a weight term changed the most.
+//!
+//! The terms are brought into their polynomial form, see [`SimpleTerm::normalize`]. Components
+//! that never appear in the same monomial do not influence each other and are solved
+//! independently. The corners of small groups of components are enumerated; larger groups are
+//! solved by coordinate ascent. Each component is additionally checked for a better value
+//! between its bounds, which only exists if the term is not multilinear.
+//!
+//! The maximal relative difference `new / old` is found with Dinkelbach's method: it repeatedly
+//! maximizes `new - λ * old` and updates `λ` to the ratio at the found point.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{scope::SimpleScope, term::SimpleTerm, Error};
+
+/// Inclusive lower and upper bound of each component by its name.
+pub type ComponentBounds = BTreeMap<String, (u32, u32)>;
+
+/// What [`find_worst`] maximizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+	/// The relative difference `new / old`.
+	Relative,
+	/// The absolute difference `new - old`.
+	Absolute,
+	/// The value of the term that exists; `new` if both exist.
+	Value,
+}
+
+/// Groups of at most this many interacting components have their corners enumerated.
+const MAX_EXACT_GROUP: usize = 12;
+/// Upper limit for the iterations of the coordinate ascent and Dinkelbach's method.
+const MAX_ITERATIONS: usize = 100;
+
+/// Finds the scope within the component bounds at which the objective is maximal.
+///
+/// The returned scope is `scope` extended by a value for each component of `bounds`. Errors if
+/// a term has a component without bounds or cannot be normalized.
+pub fn find_worst(
+	old: Option<&SimpleTerm>,
+	new: Option<&SimpleTerm>,
+	bounds: &ComponentBounds,
+	scope: &SimpleScope,
+	objective: Objective,
+) -> Result<SimpleScope, Error> {
+	let vars = bounds.keys().map(|c| c.replace('_', "")).collect::<BTreeSet<_>>();
+	let vars = vars.into_iter().collect::<Vec<_>>();
+	let mut limits = vec![(f64::MAX, f64::MIN); vars.len()];
+	for (name, (lo, hi)) in bounds {
+		let i = vars.binary_search(&name.replace('_', "")).expect("Built from the bounds; qed");
+		limits[i] = (limits[i].0.min(*lo as f64), limits[i].1.max(*hi as f64));
+	}
+	let old = old.map(|t| Polynomial::new(t, scope, &vars)).transpose()?;
+	let new = new.map(|t| Polynomial::new(t, scope, &vars)).transpose()?;
+
+	let x = match (objective, old, new) {
+		(_, None, None) => limits.iter().map(|b| b.1).collect(),
+		(Objective::Value, Some(p), None) |
+		(Objective::Value, _, Some(p)) |
+		(_, Some(p), None) |
+		(_, None, Some(p)) => p.maximize(&limits),
+		(Objective::Absolute, Some(old), Some(new)) => new.minus(&old, 1.0).maximize(&limits),
+		(Objective::Relative, Some(old), Some(new)) => max_ratio(&new, &old, &limits),
+	};
+
+	let mut scope = scope.clone();
+	for name in bounds.keys() {
+		let i = vars.binary_search(&name.replace('_', "")).expect("Built from the bounds; qed");
+		scope.put_var(name, SimpleTerm::Scalar(x[i] as u128));
+	}
+	Ok(scope)
+}
+
+/// Dinkelbach's method for maximizing `new / old`.
+fn max_ratio(new: &Polynomial, old: &Polynomial, limits: &[(f64, f64)]) -> Vec<f64> {
+	let mut x = limits.iter().map(|b| b.1).collect::<Vec<_>>();
+	for _ in 0..MAX_ITERATIONS {
+		let (n, o) = (new.eval(&x), old.eval(&x));
+		if o <= 0.0 {
+			// Any increase over zero is infinitely worse.
+			break
+		}
+		let lambda = n / o;
+		let next = new.minus(old, lambda).maximize(limits);
+		let gain = new.eval(&next) - lambda * old.eval(&next);
+		if gain <= 1e-9 * n.abs().max(1.0) {
+			break
+		}
+		x = next;
+	}
+	x
+}
+
+/// Sum of monomials with the indices of their components.
+#[derive(Clone, Debug)]
+struct Polynomial {
+	monomials: Vec<(Vec<usize>, f64)>,
+}
+
+impl Polynomial {
+	/// Normalizes the term after substituting the variables of the scope.
+	fn new(term: &SimpleTerm, scope: &SimpleScope, vars: &[String]) -> Result<Self, Error> {
+		let mut term = term.clone();
+		loop {
+			let bound = term.bound_vars(scope);
+			if bound.is_empty() {
+				break
+			}
+			for var in bound {
+				term.substitute(&var, &scope.get(&var).expect("Var is bound; qed"));
+			}
+		}
+		let coefficients = term
+			.coefficients()
+			.ok_or_else(|| Error::Eval { msg: format!("Cannot normalize term: {}", term) })?;
+
+		let mut monomials = Vec::new();
+		for (monomial, coeff) in coefficients {
+			let indices = monomial
+				.vars()
+				.iter()
+				.map(|v| {
+					vars.binary_search(&v.replace('_', ""))
+						.map_err(|_| Error::Eval { msg: format!("Variable '{}' not found", v) })
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+			monomials.push((indices, coeff as f64));
+		}
+		Ok(Self { monomials })
+	}
+
+	/// `self - factor * other`
+	fn minus(&self, other: &Self, factor: f64) -> Self {
+		let negated = other.monomials.iter().map(|(vars, c)| (vars.clone(), -factor * c));
+		Self { monomials: self.monomials.iter().cloned().chain(negated).collect() }
+	}
+
+	fn eval(&self, x: &[f64]) -> f64 {
+		self.monomials
+			.iter()
+			.map(|(vars, c)| vars.iter().fold(*c, |acc, v| acc * x[*v]))
+			.sum()
+	}
+
+	/// Finds a maximum within the box.
+	///
+	/// Exact for multilinear polynomials as long as no group of interacting components is
+	/// larger than [`MAX_EXACT_GROUP`].
+	fn maximize(&self, limits: &[(f64, f64)]) -> Vec<f64> {
+		let mut x = limits.iter().map(|b| b.1).collect::<Vec<_>>();
+		for group in self.groups(limits.len()) {
+			if group.len() <= MAX_EXACT_GROUP {
+				self.enumerate_corners(&group, limits, &mut x);
+			} else {
+				self.ascend(&group, limits, &mut x, false);
+			}
+		}
+		// Look for better values between the bounds.
+		let all = (0..limits.len()).collect::<Vec<_>>();
+		self.ascend(&all, limits, &mut x, true);
+		x
+	}
+
+	/// Partitions the components into groups that share monomials.
+	fn groups(&self, len: usize) -> Vec<Vec<usize>> {
+		fn root(parents: &mut [usize], i: usize) -> usize {
+			if parents[i] != i {
+				parents[i] = root(parents, parents[i]);
+			}
+			parents[i]
+		}
+
+		let mut parents = (0..len).collect::<Vec<_>>();
+		for (vars, _) in &self.monomials {
+			for pair in vars.windows(2) {
+				let (a, b) = (root(&mut parents, pair[0]), root(&mut parents, pair[1]));
+				parents[a] = b;
+			}
+		}
+		let mut groups = BTreeMap::<usize, Vec<usize>>::new();
+		for i in 0..len {
+			groups.entry(root(&mut parents, i)).or_default().push(i);
+		}
+		groups.into_values().collect()
+	}
+
+	fn enumerate_corners(&self, group: &[usize], limits: &[(f64, f64)], x: &mut [f64]) {
+		let mut best = (self.eval(x), x.to_vec());
+		for corner in 0..(1u32 << group.len()) {
+			for (bit, &v) in group.iter().enumerate() {
+				x[v] = if corner & (1 << bit) == 0 { limits[v].1 } else { limits[v].0 };
+			}
+			let value = self.eval(x);
+			if value > best.0 {
+				best = (value, x.to_vec());
+			}
+		}
+		for &v in group {
+			x[v] = best.1[v];
+		}
+	}
+
+	/// Improves one component at a time until no component can be improved anymore.
+	fn ascend(&self, group: &[usize], limits: &[(f64, f64)], x: &mut [f64], inner: bool) {
+		for _ in 0..MAX_ITERATIONS {
+			let mut improved = false;
+			for &v in group {
+				let mut best = (self.eval(x), x[v]);
+				for candidate in self.candidates(v, limits[v], x, inner) {
+					x[v] = candidate;
+					let value = self.eval(x);
+					if value > best.0 {
+						(best, improved) = ((value, candidate), true);
+					}
+				}
+				x[v] = best.1;
+			}
+			if !improved {
+				break
+			}
+		}
+	}
+
+	/// The bounds of a component and, if `inner`, the maximum of a quadratic in between.
+	fn candidates(&self, v: usize, (lo, hi): (f64, f64), x: &[f64], inner: bool) -> Vec<f64> {
+		let mut candidates = vec![lo, hi];
+		if !inner {
+			return candidates
+		}
+		// The coefficients of `v^0`, `v^1` and `v^2` when all other components are fixed.
+		let mut coeffs = [0.0; 3];
+		for (vars, c) in &self.monomials {
+			let degree = vars.iter().filter(|&&w| w == v).count();
+			let rest = vars.iter().filter(|&&w| w != v).fold(*c, |acc, w| acc * x[*w]);
+			match coeffs.get_mut(degree) {
+				Some(coeff) => *coeff += rest,
+				None => return candidates,
+			}
+		}
+		if coeffs[2] < 0.0 {
+			let vertex = (-coeffs[1] / (2.0 * coeffs[2])).clamp(lo, hi);
+			candidates.extend([vertex.floor(), vertex.ceil()]);
+		}
+		candidates
+	}
+}
@@ -99,7 +99,7 @@ pub(crate) fn code_link(repo_name: &str, org: &str, name: &str, file: &str, rev:
 
 pub(crate) fn html_color_percent(p: Percent, change: RelativeChange) -> String {
 	match change {
-		RelativeChange::Changed => {
+		RelativeChange::Changed | RelativeChange::Renamed => {
 			if p < 0.0 {
 				format!("<p style='color:green'>-{:.2?}%</p>", p.abs())
 			} else if p > 0.0 {
@@ -117,7 +117,7 @@ pub(crate) fn html_color_percent(p: Percent, change: RelativeChange) -> String {
 
 pub(crate) fn html_color_abs(change: &TermChange, unit: Dimension) -> String {
 	match change.change {
-		RelativeChange::Changed => {
+		RelativeChange::Changed | RelativeChange::Renamed => {
 			let diff = change.new_v.unwrap() as i128 - change.old_v.unwrap() as i128;
 			if diff < 0 {
 				format!("<p style='color:green'>-{}</p>", unit.fmt_value(diff.unsigned_abs()))
@@ -140,7 +140,7 @@ pub(crate) fn html_color_abs(change: &TermChange, unit: Dimension) -> String {
 fn order_percent(change: &TermChange) -> i128 {
 	match change.change {
 		// This only considers the first three digits of the percent since the UI only shows these.
-		RelativeChange::Changed => {
+		RelativeChange::Changed | RelativeChange::Renamed => {
 			// The ordering will be wrong for very large relative changes, but still better than
 			// having them rank above 'Added'.
 			((change.percent * 1000.0) as i128).min(u32::MAX as i128)
@@ -153,7 +153,8 @@ fn order_percent(change: &TermChange) -> i128 {
 
 fn order_abs(change: &TermChange) -> i128 {
 	match change.change {
-		RelativeChange::Changed => change.new_v.unwrap() as i128 - change.old_v.unwrap() as i128,
+		RelativeChange::Changed | RelativeChange::Renamed =>
+			change.new_v.unwrap() as i128 - change.old_v.unwrap() as i128,
 		RelativeChange::Unchanged => 0,
 		RelativeChange::Added => i128::MAX,
 		RelativeChange::Removed => i128::MIN,
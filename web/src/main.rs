@@ -419,8 +419,20 @@ fn do_compare_cached(
 		args.git_pull.unwrap_or(true),
 	);
 
-	let params =
-		CompareParams { method, ignore_errors, unit, git_pull, offline: false, git_force: true };
+	let params = CompareParams {
+		method,
+		ignore_errors,
+		unit,
+		chromatic: None,
+		budget: Default::default(),
+		db: Default::default(),
+		overhead: Default::default(),
+		impls: Default::default(),
+		renames: Default::default(),
+		git_pull,
+		offline: false,
+		git_force: true,
+	};
 	let filter = FilterParams {
 		threshold: args.threshold as f64,
 		change: None,